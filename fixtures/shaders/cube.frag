@@ -0,0 +1,8 @@
+#version 450
+layout(location = 0) in vec3 vNormal;
+
+layout(location = 0) out vec4 f_color;
+
+void main() {
+    f_color = vec4(normalize(vNormal) * 0.5 + 0.5, 1.0);
+}
@@ -0,0 +1,15 @@
+#version 450
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec3 normal;
+
+layout(location = 0) out vec3 vNormal;
+
+layout(set = 0, binding = 0) uniform UniformMatrices {
+    mat4 view_projection;
+} uniforms;
+
+void main() {
+    vNormal = normal;
+
+    gl_Position = uniforms.view_projection * vec4(position, 1.0);
+}
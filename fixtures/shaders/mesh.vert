@@ -0,0 +1,23 @@
+#version 450
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec2 uv;
+
+layout(location = 0) out vec3 vNormal;
+layout(location = 1) out vec2 vUV;
+
+layout(set = 0, binding = 0) uniform UniformMatrices {
+    mat4 view_projection;
+} uniforms;
+
+layout(push_constant) uniform MeshConstants {
+    mat4 node;
+    vec4 base_color;
+} mesh;
+
+void main() {
+    vNormal = mat3(mesh.node) * normal;
+    vUV = uv;
+
+    gl_Position = uniforms.view_projection * mesh.node * vec4(position, 1.0);
+}
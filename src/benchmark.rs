@@ -0,0 +1,68 @@
+use std::fmt;
+use std::time::Duration;
+
+
+// Frames rendered before measuring starts, so pipeline creation, first uploads and the
+// driver warming up don't count.
+pub const WARMUP_FRAMES: usize = 60;
+
+// CPU time per frame, as spent in `Renderer::draw_frame`: waiting for a free frame in
+// flight, recording and submitting.
+pub struct FrameStats {
+    pub warmup: usize,
+    skipped: usize,
+    samples: Vec<Duration>
+}
+
+impl FrameStats {
+    pub fn new(warmup: usize) -> FrameStats {
+        FrameStats {
+            warmup: warmup,
+            skipped: 0,
+            samples: Vec::new()
+        }
+    }
+
+    pub fn record(&mut self, time: Duration) {
+        if self.skipped < self.warmup {
+            self.skipped += 1;
+        } else {
+            self.samples.push(time);
+        }
+    }
+
+    // Frames measured so far, not counting the warmup.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn mean(&self) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::from_secs(0);
+        }
+        self.samples.iter().sum::<Duration>() / self.samples.len() as u32
+    }
+
+    // Nearest-rank percentile, `fraction` from 0 to 1.
+    pub fn percentile(&self, fraction: f32) -> Duration {
+        let mut sorted = self.samples.clone();
+        sorted.sort();
+        let rank = (fraction.clamp(0.0, 1.0) * sorted.len() as f32).ceil() as usize;
+        sorted.get(rank.max(1) - 1).cloned().unwrap_or_else(|| Duration::from_secs(0))
+    }
+}
+
+fn millis(time: Duration) -> f64 {
+    time.as_secs_f64() * 1000.0
+}
+
+impl fmt::Display for FrameStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} frames, CPU frame time mean {:.3} ms, median {:.3} ms, 99th percentile {:.3} ms, max {:.3} ms",
+            self.len(), millis(self.mean()), millis(self.percentile(0.5)), millis(self.percentile(0.99)), millis(self.percentile(1.0)))
+    }
+}
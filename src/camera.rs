@@ -0,0 +1,77 @@
+use cgmath::{Point3, Vector3, Matrix4, Rad, Angle, InnerSpace};
+
+
+// World up. The terrain rises towards -y, the projection flips y to match Vulkan's clip space.
+pub const UP: Vector3<f32> = Vector3 {
+    x: 0.0,
+    y: -1.0,
+    z: 0.0
+};
+
+// Just short of straight up or down, where the view matrix would degenerate.
+const MAX_PITCH: Rad<f32> = Rad(1.55);
+
+// First-person camera. With all angles at zero it looks along -z, positive yaw turns right,
+// positive pitch looks up and roll turns the view around the looking direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub position: Point3<f32>,
+    pub yaw: Rad<f32>,
+    pub pitch: Rad<f32>,
+    pub roll: Rad<f32>
+}
+
+impl Camera {
+    pub fn new(position: Point3<f32>, yaw: Rad<f32>, pitch: Rad<f32>) -> Camera {
+        Camera {
+            position: position,
+            yaw: yaw,
+            pitch: pitch,
+            roll: Rad(0.0)
+        }
+    }
+
+    pub fn look_at(position: Point3<f32>, target: Point3<f32>) -> Camera {
+        let direction = (target - position).normalize();
+        let yaw = Rad((-direction.x).atan2(-direction.z));
+        let pitch = Rad((-direction.y).asin());
+        Camera::new(position, yaw, pitch)
+    }
+
+    pub fn forward(&self) -> Vector3<f32> {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        Vector3::new(-sin_yaw * cos_pitch, -sin_pitch, -cos_yaw * cos_pitch)
+    }
+
+    // `forward` without the pitch, for moving along the ground.
+    pub fn flat_forward(&self) -> Vector3<f32> {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        Vector3::new(-sin_yaw, 0.0, -cos_yaw)
+    }
+
+    pub fn right(&self) -> Vector3<f32> {
+        self.flat_forward().cross(UP)
+    }
+
+    pub fn view(&self) -> Matrix4<f32> {
+        Matrix4::from_angle_z(self.roll) * Matrix4::look_at_dir(self.position, self.forward(), UP)
+    }
+
+    pub fn translate(&mut self, offset: Vector3<f32>) {
+        self.position += offset;
+    }
+
+    pub fn turn(&mut self, angle: Rad<f32>) {
+        self.yaw = (self.yaw + angle).normalize();
+    }
+
+    pub fn look_up(&mut self, angle: Rad<f32>) {
+        let pitch = self.pitch + angle;
+        self.pitch = if pitch > MAX_PITCH { MAX_PITCH } else if pitch < -MAX_PITCH { -MAX_PITCH } else { pitch };
+    }
+
+    pub fn tilt(&mut self, angle: Rad<f32>) {
+        self.roll = (self.roll + angle).normalize();
+    }
+}
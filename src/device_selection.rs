@@ -0,0 +1,462 @@
+use std::env;
+use std::fmt;
+use std::mem;
+use std::sync::Arc;
+
+use vulkano::device::Device;
+use vulkano::device::DeviceExtensions;
+use vulkano::device::Features;
+use vulkano::device::Queue;
+use vulkano::format::Format;
+use vulkano::instance::Instance;
+use vulkano::instance::PhysicalDevice;
+use vulkano::instance::PhysicalDeviceType;
+use vulkano::instance::Version;
+use vulkano::instance::loader;
+use vulkano::swapchain::Surface;
+use vulkano::sync::SharingMode;
+use vulkano::VulkanObject;
+use vk_sys as vk;
+use winit::Window;
+
+use error::VulrustError;
+use headless;
+
+
+// Environment variable picking the device when `--gpu` isn't given.
+pub const DEVICE_ENV_VAR: &str = "VULRUST_GPU";
+
+const MIB: usize = 1024 * 1024;
+
+// Which device the user asked for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DevicePreference {
+    // The suitable device with the best score.
+    Auto,
+    // Position in the enumeration, as printed in the report.
+    Index(usize),
+    // Case-insensitive part of the device name, e.g. `nvidia` or `llvmpipe`.
+    Name(String)
+}
+
+impl DevicePreference {
+    // `auto` or nothing, a device index or a part of a device name.
+    pub fn parse(value: &str) -> DevicePreference {
+        let value = value.trim();
+        if value.is_empty() || value.eq_ignore_ascii_case("auto") {
+            return DevicePreference::Auto;
+        }
+        match value.parse() {
+            Ok(index) => DevicePreference::Index(index),
+            Err(_) => DevicePreference::Name(value.to_string())
+        }
+    }
+
+    pub fn from_env() -> DevicePreference {
+        env::var(DEVICE_ENV_VAR).map(|value| DevicePreference::parse(&value)).unwrap_or(DevicePreference::Auto)
+    }
+
+    pub fn matches(&self, device: &DeviceInfo) -> bool {
+        match *self {
+            DevicePreference::Auto => true,
+            DevicePreference::Index(index) => device.index == index,
+            DevicePreference::Name(ref name) => device.name.to_lowercase().contains(&name.to_lowercase())
+        }
+    }
+}
+
+impl fmt::Display for DevicePreference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DevicePreference::Auto => write!(f, "auto"),
+            DevicePreference::Index(index) => write!(f, "{}", index),
+            DevicePreference::Name(ref name) => write!(f, "{}", name)
+        }
+    }
+}
+
+// What an image format can be used for with optimal tiling. Only what the renderer needs.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FormatFeatures {
+    pub sampled_image: bool,
+    pub sampled_image_filter_linear: bool,
+    pub color_attachment: bool,
+    pub transfer_source: bool
+}
+
+impl FormatFeatures {
+    // Before Vulkan 1.1 every format can be copied from, so there's no bit for it to check.
+    pub fn from_bits(bits: u32, api_version: Version) -> FormatFeatures {
+        FormatFeatures {
+            sampled_image: bits & vk::FORMAT_FEATURE_SAMPLED_IMAGE_BIT != 0,
+            sampled_image_filter_linear: bits & vk::FORMAT_FEATURE_SAMPLED_IMAGE_FILTER_LINEAR_BIT != 0,
+            color_attachment: bits & vk::FORMAT_FEATURE_COLOR_ATTACHMENT_BIT != 0,
+            transfer_source: bits & vk::FORMAT_FEATURE_TRANSFER_SRC_BIT_KHR != 0 || api_version < Version { major: 1, minor: 1, patch: 0 }
+        }
+    }
+
+    pub fn superset_of(&self, other: &FormatFeatures) -> bool {
+        (self.sampled_image || !other.sampled_image) &&
+            (self.sampled_image_filter_linear || !other.sampled_image_filter_linear) &&
+            (self.color_attachment || !other.color_attachment) &&
+            (self.transfer_source || !other.transfer_source)
+    }
+
+    // What `physical` supports for `format`. vulkano doesn't expose the query, so it goes
+    // through the loader the instance was created with.
+    pub fn of(physical: PhysicalDevice, format: Format) -> FormatFeatures {
+        let loader = match loader::auto_loader() {
+            Ok(loader) => loader,
+            Err(_) => return FormatFeatures::default()
+        };
+        let instance = physical.instance().internal_object();
+        let name = b"vkGetPhysicalDeviceFormatProperties\0";
+        unsafe {
+            // A Vulkan 1.0 entry point, every implementation has it.
+            let get_properties: extern "system" fn(vk::PhysicalDevice, vk::Format, *mut vk::FormatProperties) =
+                mem::transmute(loader.get_instance_proc_addr(instance, name.as_ptr() as *const _));
+            let mut properties = vk::FormatProperties { linearTilingFeatures: 0, optimalTilingFeatures: 0, bufferFeatures: 0 };
+            get_properties(physical.internal_object(), format as u32, &mut properties);
+            FormatFeatures::from_bits(properties.optimalTilingFeatures, physical.api_version())
+        }
+    }
+}
+
+// The heightmap is uploaded as `R16Unorm` and sampled with a linear filter, which Vulkan
+// doesn't require for that format.
+fn heightmap_format() -> (Format, FormatFeatures) {
+    (Format::R16Unorm, FormatFeatures { sampled_image: true, sampled_image_filter_linear: true, .. FormatFeatures::default() })
+}
+
+// What a device needs to offer to run the renderer. The depth format is one every
+// implementation has to support and the window's color format comes from the surface.
+#[derive(Debug, Clone)]
+pub struct DeviceRequirements {
+    pub features: Features,
+    pub extensions: DeviceExtensions,
+    pub formats: Vec<(Format, FormatFeatures)>
+}
+
+impl DeviceRequirements {
+    // Drawing into a window.
+    pub fn windowed() -> DeviceRequirements {
+        DeviceRequirements {
+            features: Features::none(),
+            extensions: DeviceExtensions { khr_swapchain: true, .. DeviceExtensions::none() },
+            formats: vec![heightmap_format()]
+        }
+    }
+
+    // Drawing into an image, see `headless`, and copying it out.
+    pub fn headless() -> DeviceRequirements {
+        let color = FormatFeatures { color_attachment: true, transfer_source: true, .. FormatFeatures::default() };
+        DeviceRequirements {
+            features: Features::none(),
+            extensions: DeviceExtensions::none(),
+            formats: vec![heightmap_format(), (headless::COLOR_FORMAT, color)]
+        }
+    }
+}
+
+// Why a device can't be used.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Unsuitable {
+    NoGraphicsQueue,
+    // No queue family can present to the surface.
+    CannotPresent,
+    NoSurfaceFormats,
+    MissingFeatures,
+    MissingExtensions(String),
+    // Can't be used the way the renderer uses it.
+    UnsupportedFormat(Format),
+    Surface(String)
+}
+
+impl fmt::Display for Unsuitable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Unsuitable::NoGraphicsQueue => write!(f, "no graphics queue"),
+            Unsuitable::CannotPresent => write!(f, "can't present to the window"),
+            Unsuitable::NoSurfaceFormats => write!(f, "no format to present in"),
+            Unsuitable::MissingFeatures => write!(f, "missing required features"),
+            Unsuitable::MissingExtensions(ref extensions) => write!(f, "missing extensions {}", extensions),
+            Unsuitable::UnsupportedFormat(format) => write!(f, "no support for {:?} images as needed", format),
+            Unsuitable::Surface(ref err) => write!(f, "couldn't query the surface: {}", err)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueueFamilyInfo {
+    pub id: u32,
+    pub queues: usize,
+    pub graphics: bool,
+    pub compute: bool,
+    pub transfers: bool,
+    pub present: bool
+}
+
+// Queue family picked for each job, one family may do several.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QueueFamilies {
+    pub graphics: u32,
+    pub present: u32,
+    // Uploads. Preferably a family that only transfers, so copies run alongside drawing.
+    pub transfer: u32
+}
+
+impl QueueFamilies {
+    // `None` without a graphics family, or without any family that presents when `present`
+    // is asked for. Drawing and presenting share a family whenever one can do both.
+    pub fn assign(families: &[QueueFamilyInfo], present: bool) -> Option<QueueFamilies> {
+        let graphics = families.iter().find(|family| family.graphics && (family.present || !present))
+            .or_else(|| families.iter().find(|family| family.graphics))?;
+        let presenting = if !present || graphics.present {
+            graphics
+        } else {
+            families.iter().find(|family| family.present)?
+        };
+        // Graphics families can always transfer, even without saying so.
+        let transfer = families.iter().find(|family| family.transfers && !family.graphics && !family.compute)
+            .or_else(|| families.iter().find(|family| family.transfers && !family.graphics))
+            .unwrap_or(graphics);
+        Some(QueueFamilies {
+            graphics: graphics.id,
+            present: presenting.id,
+            transfer: transfer.id
+        })
+    }
+
+    // Families that use uploaded buffers and images: they're filled on the transfer family and
+    // drawn from on the graphics one.
+    pub fn upload_families(&self) -> Vec<u32> {
+        let mut ids = vec![self.transfer];
+        if self.graphics != self.transfer {
+            ids.push(self.graphics);
+        }
+        ids
+    }
+
+    // Every family once, the order queues are created in.
+    pub fn unique(&self) -> Vec<u32> {
+        let mut ids = vec![self.graphics];
+        for &id in [self.present, self.transfer].iter() {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+}
+
+// One queue per job, the same queue where jobs share a family.
+#[derive(Debug, Clone)]
+pub struct Queues {
+    pub graphics: Arc<Queue>,
+    pub present: Arc<Queue>,
+    pub transfer: Arc<Queue>,
+    pub families: QueueFamilies
+}
+
+impl Queues {
+    // Swapchain images are drawn on one queue and presented on the other.
+    pub fn swapchain_sharing(&self) -> SharingMode {
+        if self.graphics.family().id() == self.present.family().id() {
+            SharingMode::from(&self.graphics)
+        } else {
+            SharingMode::from(&[&self.graphics, &self.present][..])
+        }
+    }
+}
+
+// What the report shows of a device and what the selection goes by.
+#[derive(Debug, Clone)]
+pub struct DeviceInfo {
+    pub index: usize,
+    pub name: String,
+    pub ty: PhysicalDeviceType,
+    pub api_version: Version,
+    pub driver_version: u32,
+    pub vendor_id: u32,
+    pub device_id: u32,
+    // Bytes of device-local memory over all heaps.
+    pub device_memory: usize,
+    pub queue_families: Vec<QueueFamilyInfo>,
+    // Families to use, `None` if the device can't draw (or present).
+    pub assigned: Option<QueueFamilies>,
+    // Empty for a device that can be used.
+    pub problems: Vec<Unsuitable>
+}
+
+impl DeviceInfo {
+    // Looks at what `physical` offers against `requirements`. Without a surface presenting
+    // isn't checked.
+    pub fn inspect(physical: PhysicalDevice, requirements: &DeviceRequirements, surface: Option<&Surface<Window>>) -> DeviceInfo {
+        let mut problems = Vec::new();
+
+        let mut queue_families = Vec::new();
+        for family in physical.queue_families() {
+            let present = match surface {
+                Some(surface) => match surface.is_supported(family) {
+                    Ok(present) => present,
+                    Err(err) => {
+                        problems.push(Unsuitable::Surface(err.to_string()));
+                        false
+                    }
+                },
+                None => false
+            };
+            queue_families.push(QueueFamilyInfo {
+                id: family.id(),
+                queues: family.queues_count(),
+                graphics: family.supports_graphics(),
+                compute: family.supports_compute(),
+                transfers: family.supports_transfers(),
+                present: present
+            });
+        }
+
+        let assigned = QueueFamilies::assign(&queue_families, surface.is_some());
+        if !queue_families.iter().any(|family| family.graphics) {
+            problems.push(Unsuitable::NoGraphicsQueue);
+        } else if assigned.is_none() {
+            problems.push(Unsuitable::CannotPresent);
+        }
+
+        if let Some(surface) = surface {
+            match surface.capabilities(physical) {
+                Ok(caps) => if caps.supported_formats.is_empty() { problems.push(Unsuitable::NoSurfaceFormats) },
+                Err(err) => problems.push(Unsuitable::Surface(err.to_string()))
+            }
+        }
+
+        if !physical.supported_features().superset_of(&requirements.features) {
+            problems.push(Unsuitable::MissingFeatures);
+        }
+        let missing = requirements.extensions.difference(&DeviceExtensions::supported_by_device(physical));
+        if missing != DeviceExtensions::none() {
+            problems.push(Unsuitable::MissingExtensions(format!("{:?}", missing)));
+        }
+        for &(format, ref features) in requirements.formats.iter() {
+            if !FormatFeatures::of(physical, format).superset_of(features) {
+                problems.push(Unsuitable::UnsupportedFormat(format));
+            }
+        }
+
+        DeviceInfo {
+            index: physical.index(),
+            name: physical.name(),
+            ty: physical.ty(),
+            api_version: physical.api_version(),
+            driver_version: physical.driver_version(),
+            vendor_id: physical.pci_vendor_id(),
+            device_id: physical.pci_device_id(),
+            device_memory: physical.memory_heaps().filter(|heap| heap.is_device_local()).map(|heap| heap.size()).sum(),
+            queue_families: queue_families,
+            assigned: assigned,
+            problems: problems
+        }
+    }
+
+    pub fn suitable(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    // Discrete GPUs first, software renderers last. More memory wins between devices of a kind.
+    pub fn score(&self) -> (u32, usize) {
+        let kind = match self.ty {
+            PhysicalDeviceType::DiscreteGpu => 4,
+            PhysicalDeviceType::IntegratedGpu => 3,
+            PhysicalDeviceType::VirtualGpu => 2,
+            PhysicalDeviceType::Other => 1,
+            PhysicalDeviceType::Cpu => 0
+        };
+        (kind, self.device_memory)
+    }
+}
+
+impl fmt::Display for DeviceInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "GPU {}: {} ({:?}), Vulkan {}, driver {:#x}, PCI {:04x}:{:04x}",
+            self.index, self.name, self.ty, self.api_version, self.driver_version, self.vendor_id, self.device_id)?;
+        writeln!(f, "  device memory: {} MiB", self.device_memory / MIB)?;
+        for family in self.queue_families.iter() {
+            let capabilities: Vec<&str> = [(family.graphics, "graphics"), (family.compute, "compute"), (family.transfers, "transfer"), (family.present, "present")]
+                .iter()
+                .filter(|&&(supported, _)| supported)
+                .map(|&(_, name)| name)
+                .collect();
+            writeln!(f, "  queue family {}: {} queues, {}", family.id, family.queues, capabilities.join(" "))?;
+        }
+        if let Some(assigned) = self.assigned {
+            writeln!(f, "  queues: graphics {}, present {}, transfer {}", assigned.graphics, assigned.present, assigned.transfer)?;
+        }
+        if self.suitable() {
+            write!(f, "  usable, score {:?}", self.score())
+        } else {
+            let problems: Vec<String> = self.problems.iter().map(|problem| problem.to_string()).collect();
+            write!(f, "  unusable: {}", problems.join(", "))
+        }
+    }
+}
+
+// The device `preference` asks for, or the best suitable one. A device asked for by index or
+// name has to be suitable, there's no falling back to another one.
+pub fn choose<'a>(devices: &'a [DeviceInfo], preference: &DevicePreference) -> Result<&'a DeviceInfo, VulrustError> {
+    if *preference != DevicePreference::Auto {
+        let device = devices.iter().find(|device| preference.matches(device))
+            .ok_or_else(|| VulrustError::DeviceNotFound(preference.to_string()))?;
+        if !device.suitable() {
+            return Err(VulrustError::UnsuitableDevice(device.name.clone(), device.problems.clone()));
+        }
+        return Ok(device);
+    }
+
+    let mut best: Option<&DeviceInfo> = None;
+    for device in devices.iter().filter(|device| device.suitable()) {
+        // Ties go to the first device.
+        if best.is_none_or(|best| device.score() > best.score()) {
+            best = Some(device);
+        }
+    }
+    best.ok_or(VulrustError::NoPhysicalDevice)
+}
+
+// Prints a report of every device and picks one with `choose`, returning it with the queue
+// families to use.
+pub fn select_device<'a>(instance: &'a Arc<Instance>, requirements: &DeviceRequirements, surface: Option<&Surface<Window>>, preference: &DevicePreference)
+    -> Result<(PhysicalDevice<'a>, QueueFamilies), VulrustError>
+{
+    let devices: Vec<DeviceInfo> = PhysicalDevice::enumerate(instance)
+        .map(|physical| DeviceInfo::inspect(physical, requirements, surface))
+        .collect();
+    for device in devices.iter() {
+        println!("{}", device);
+    }
+
+    let device = choose(&devices, preference)?;
+    println!("Using GPU {}: {}", device.index, device.name);
+    let physical = PhysicalDevice::from_index(instance, device.index).ok_or(VulrustError::NoPhysicalDevice)?;
+    let families = device.assigned.ok_or(VulrustError::NoQueueFamily)?;
+    Ok((physical, families))
+}
+
+// Creates the device with a queue in each family of `families`.
+pub fn create_device(physical: PhysicalDevice, requirements: &DeviceRequirements, families: QueueFamilies) -> Result<(Arc<Device>, Queues), VulrustError> {
+    let mut requests = Vec::new();
+    for id in families.unique() {
+        let family = physical.queue_family_by_id(id).ok_or(VulrustError::NoQueueFamily)?;
+        requests.push((family, 1.0));
+    }
+    let (device, queues) = Device::new(physical, &requirements.features, &requirements.extensions, requests)?;
+
+    let queues: Vec<Arc<Queue>> = queues.collect();
+    let queue = |id: u32| queues.iter().find(|queue| queue.family().id() == id).cloned().ok_or(VulrustError::NoQueueFamily);
+    let queues = Queues {
+        graphics: queue(families.graphics)?,
+        present: queue(families.present)?,
+        transfer: queue(families.transfer)?,
+        families: families
+    };
+    Ok((device, queues))
+}
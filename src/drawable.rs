@@ -0,0 +1,101 @@
+use std::cell::RefCell;
+use std::sync::Arc;
+use vulkano::buffer::BufferAccess;
+use vulkano::buffer::cpu_access::CpuAccessibleBuffer;
+use vulkano::command_buffer::AutoCommandBufferBuilder;
+use vulkano::command_buffer::DynamicState;
+
+use error::VulrustError;
+use shader_reload::{ShaderError, ShaderLibrary};
+use shader_utils;
+use world::World;
+
+
+// Uniforms shared by every drawable within a frame.
+pub struct FrameUniforms {
+    // `UniformMatrices` buffer bound at set 0, binding 0 of every pipeline.
+    pub world: Arc<BufferAccess + Send + Sync>,
+    // Which frame in flight this is. `world` is always the same buffer for the same frame.
+    pub frame: usize,
+    pub delta: f32,
+    // How far the frame lies between the last two fixed updates, 0 to 1.
+    pub alpha: f32
+}
+
+impl FrameUniforms {
+    // Writes the matrices of `world` into the uniform buffer of frame in flight `frame`, which
+    // the GPU must be done with.
+    pub fn new(buffer: &Arc<CpuAccessibleBuffer<shader_utils::vs::ty::UniformMatrices>>, frame: usize, world: &World) -> Result<FrameUniforms, VulrustError> {
+        *buffer.write()? = shader_utils::vs::ty::UniformMatrices {
+            view_projection: world.view_projection().into()
+        };
+
+        Ok(FrameUniforms {
+            world: buffer.clone(),
+            frame: frame,
+            delta: world.spin(),
+            alpha: world.alpha
+        })
+    }
+}
+
+// What a drawable binds for each frame in flight, like its descriptor set of
+// `FrameUniforms::world`. Built on first use and reused every time that frame comes round,
+// until `clear` drops it, e.g. for a new pipeline.
+pub struct FrameCache<T> {
+    entries: RefCell<Vec<Option<T>>>
+}
+
+impl<T: Clone> FrameCache<T> {
+    pub fn new() -> FrameCache<T> {
+        FrameCache {
+            entries: RefCell::new(Vec::new())
+        }
+    }
+
+    pub fn get_or_build<F>(&self, frame: usize, build: F) -> Result<T, VulrustError>
+        where F: FnOnce() -> Result<T, VulrustError>
+    {
+        if let Some(Some(entry)) = self.entries.borrow().get(frame) {
+            return Ok(entry.clone());
+        }
+        let entry = build()?;
+        let mut entries = self.entries.borrow_mut();
+        if entries.len() <= frame {
+            entries.resize(frame + 1, None);
+        }
+        entries[frame] = Some(entry.clone());
+        Ok(entry)
+    }
+
+    // How many frames have an entry.
+    pub fn built(&self) -> usize {
+        self.entries.borrow().iter().filter(|entry| entry.is_some()).count()
+    }
+
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear();
+    }
+}
+
+impl<T: Clone> Default for FrameCache<T> {
+    fn default() -> FrameCache<T> {
+        FrameCache::new()
+    }
+}
+
+// Anything that can record its own draw commands into a render pass.
+pub trait Drawable {
+    fn draw(&self, command_buffer_builder: AutoCommandBufferBuilder, dynamic_state: &DynamicState, uniforms: &FrameUniforms) -> Result<AutoCommandBufferBuilder, VulrustError>;
+
+    // Rebuilds the pipeline with whatever shaders `library` has for this drawable. On error the
+    // old pipeline stays in place.
+    fn reload_shaders(&mut self, library: &ShaderLibrary) -> Result<(), ShaderError>;
+}
+
+pub fn draw_all(mut command_buffer_builder: AutoCommandBufferBuilder, dynamic_state: &DynamicState, uniforms: &FrameUniforms, drawables: &[Box<Drawable>]) -> Result<AutoCommandBufferBuilder, VulrustError> {
+    for drawable in drawables.iter() {
+        command_buffer_builder = drawable.draw(command_buffer_builder, dynamic_state, uniforms)?;
+    }
+    Ok(command_buffer_builder)
+}
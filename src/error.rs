@@ -0,0 +1,205 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use vulkano::OomError;
+use vulkano::buffer::cpu_access::{ReadLockError, WriteLockError};
+use vulkano::command_buffer::{AutoCommandBufferBuilderContextError, BeginRenderPassError, BuildError, CommandBufferExecError};
+use vulkano::command_buffer::{CopyBufferError, CopyBufferImageError, DrawError, DrawIndexedError};
+use vulkano::descriptor::descriptor_set::{PersistentDescriptorSetBuildError, PersistentDescriptorSetError};
+use vulkano::device::DeviceCreationError;
+use vulkano::framebuffer::{FramebufferCreationError, RenderPassCreationError};
+use vulkano::image::ImageCreationError;
+use vulkano::instance::{InstanceCreationError, LoadingError};
+use vulkano::instance::debug::DebugCallbackCreationError;
+use vulkano::memory::DeviceMemoryAllocError;
+use vulkano::pipeline::GraphicsPipelineCreationError;
+use vulkano::sampler::SamplerCreationError;
+use vulkano::swapchain::{AcquireError, CapabilitiesError, SwapchainCreationError};
+use vulkano::sync::FlushError;
+use vulkano_win;
+
+use device_selection::Unsuitable;
+use gltf_loader::GltfError;
+use input::InputMapError;
+use obj_loader::ObjError;
+use shader_reload::ShaderError;
+use terrain::HeightmapError;
+
+
+// Everything that can go wrong setting up Vulkan, loading assets and rendering frames.
+#[derive(Debug)]
+pub enum VulrustError {
+    // A command line option or environment variable with a value it can't take.
+    InvalidArgument { name: String, value: String },
+    Instance(InstanceCreationError),
+    DebugCallback(DebugCallbackCreationError),
+    // Errors reported by the validation layer in `ValidationMode::Strict`.
+    Validation(usize),
+    // `ValidationMode::Strict` without the validation layer or the debug report extension.
+    ValidationUnavailable(&'static str),
+    // No device that meets the requirements.
+    NoPhysicalDevice,
+    // `--gpu` or `VULRUST_GPU` names no device.
+    DeviceNotFound(String),
+    UnsuitableDevice(String, Vec<Unsuitable>),
+    // No queue family that can do what the renderer needs.
+    NoQueueFamily,
+    Device(DeviceCreationError),
+    Window(vulkano_win::CreationError),
+    // The device can't present to the window's surface, or in no format.
+    SurfaceUnsupported,
+    Surface(CapabilitiesError),
+    Swapchain(SwapchainCreationError),
+
+    OutOfMemory(DeviceMemoryAllocError),
+    Image(ImageCreationError),
+    Sampler(SamplerCreationError),
+    RenderPass(RenderPassCreationError),
+    Framebuffer(FramebufferCreationError),
+    Pipeline(GraphicsPipelineCreationError),
+    DescriptorSet(PersistentDescriptorSetError),
+    DescriptorSetBuild(PersistentDescriptorSetBuildError),
+    // Something a drawable needs wasn't created yet, e.g. drawing before `create_cube`.
+    Missing(&'static str),
+
+    Acquire(AcquireError),
+    BeginRenderPass(BeginRenderPassError),
+    Draw(DrawError),
+    DrawIndexed(DrawIndexedError),
+    Commands(AutoCommandBufferBuilderContextError),
+    CopyBuffer(CopyBufferError),
+    CopyImage(CopyBufferImageError),
+    BuildCommands(BuildError),
+    Execute(CommandBufferExecError),
+    Flush(FlushError),
+    ReadBack(ReadLockError),
+    // A uniform buffer the GPU still uses, see `FramesInFlight`.
+    Uniforms(WriteLockError),
+
+    Shader(ShaderError),
+    Heightmap(HeightmapError),
+    Obj(ObjError),
+    Gltf(GltfError),
+    InputMap(InputMapError),
+    Io(PathBuf, io::Error)
+}
+
+impl fmt::Display for VulrustError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            VulrustError::InvalidArgument { ref name, ref value } => write!(f, "invalid value {:?} for {}", value, name),
+            VulrustError::Instance(ref err) => write!(f, "couldn't create the Vulkan instance: {}", err),
+            VulrustError::DebugCallback(ref err) => write!(f, "couldn't install the debug callback: {}", err),
+            VulrustError::Validation(errors) => write!(f, "the validation layer reported {} error(s)", errors),
+            VulrustError::ValidationUnavailable(what) => write!(f, "strict validation needs {}, which isn't available", what),
+            VulrustError::NoPhysicalDevice => write!(f, "no Vulkan device can run the renderer"),
+            VulrustError::DeviceNotFound(ref preference) => write!(f, "no Vulkan device matches {:?}", preference),
+            VulrustError::UnsuitableDevice(ref name, ref problems) => {
+                let problems: Vec<String> = problems.iter().map(|problem| problem.to_string()).collect();
+                write!(f, "{} can't run the renderer: {}", name, problems.join(", "))
+            },
+            VulrustError::NoQueueFamily => write!(f, "the device has no suitable queue family"),
+            VulrustError::Device(ref err) => write!(f, "couldn't create the device: {}", err),
+            VulrustError::Window(ref err) => write!(f, "couldn't create the window: {}", err),
+            VulrustError::SurfaceUnsupported => write!(f, "the device can't present to the window"),
+            VulrustError::Surface(ref err) => write!(f, "couldn't query the surface: {}", err),
+            VulrustError::Swapchain(ref err) => write!(f, "couldn't create the swapchain: {}", err),
+            VulrustError::OutOfMemory(ref err) => write!(f, "couldn't allocate memory: {}", err),
+            VulrustError::Image(ref err) => write!(f, "couldn't create an image: {}", err),
+            VulrustError::Sampler(ref err) => write!(f, "couldn't create a sampler: {}", err),
+            VulrustError::RenderPass(ref err) => write!(f, "couldn't create the render pass: {}", err),
+            VulrustError::Framebuffer(ref err) => write!(f, "couldn't create a framebuffer: {}", err),
+            VulrustError::Pipeline(ref err) => write!(f, "couldn't build a pipeline: {}", err),
+            VulrustError::DescriptorSet(ref err) => write!(f, "couldn't fill a descriptor set: {}", err),
+            VulrustError::DescriptorSetBuild(ref err) => write!(f, "couldn't build a descriptor set: {}", err),
+            VulrustError::Missing(what) => write!(f, "missing {}", what),
+            VulrustError::Acquire(ref err) => write!(f, "couldn't acquire a swapchain image: {}", err),
+            VulrustError::BeginRenderPass(ref err) => write!(f, "couldn't begin the render pass: {}", err),
+            VulrustError::Draw(ref err) => write!(f, "couldn't record a draw: {}", err),
+            VulrustError::DrawIndexed(ref err) => write!(f, "couldn't record a draw: {}", err),
+            VulrustError::Commands(ref err) => write!(f, "couldn't record commands: {}", err),
+            VulrustError::CopyBuffer(ref err) => write!(f, "couldn't record a buffer copy: {}", err),
+            VulrustError::CopyImage(ref err) => write!(f, "couldn't record an image copy: {}", err),
+            VulrustError::BuildCommands(ref err) => write!(f, "couldn't build a command buffer: {}", err),
+            VulrustError::Execute(ref err) => write!(f, "couldn't submit a command buffer: {}", err),
+            VulrustError::Flush(ref err) => write!(f, "couldn't flush the frame: {}", err),
+            VulrustError::ReadBack(ref err) => write!(f, "couldn't read back the frame: {}", err),
+            VulrustError::Uniforms(ref err) => write!(f, "couldn't update uniforms: {}", err),
+            VulrustError::Shader(ref err) => write!(f, "shader: {}", err),
+            VulrustError::Heightmap(ref err) => write!(f, "heightmap: {}", err),
+            VulrustError::Obj(ref err) => write!(f, "OBJ: {}", err),
+            VulrustError::Gltf(ref err) => write!(f, "glTF: {}", err),
+            VulrustError::InputMap(ref err) => write!(f, "input config: {}", err),
+            VulrustError::Io(ref path, ref err) => write!(f, "{}: {}", path.display(), err)
+        }
+    }
+}
+
+impl VulrustError {
+    pub fn invalid_argument(name: &str, value: &str) -> VulrustError {
+        VulrustError::InvalidArgument { name: name.to_string(), value: value.to_string() }
+    }
+}
+
+impl error::Error for VulrustError {}
+
+// Lets `?` wrap the errors of the calls each variant is named after.
+macro_rules! from_errors {
+    ($($variant:ident($error:ty)),*) => {
+        $(
+            impl From<$error> for VulrustError {
+                fn from(err: $error) -> VulrustError {
+                    VulrustError::$variant(err)
+                }
+            }
+        )*
+    }
+}
+
+from_errors!(
+    Instance(InstanceCreationError),
+    DebugCallback(DebugCallbackCreationError),
+    Device(DeviceCreationError),
+    Window(vulkano_win::CreationError),
+    Surface(CapabilitiesError),
+    Swapchain(SwapchainCreationError),
+    OutOfMemory(DeviceMemoryAllocError),
+    Image(ImageCreationError),
+    Sampler(SamplerCreationError),
+    RenderPass(RenderPassCreationError),
+    Framebuffer(FramebufferCreationError),
+    Pipeline(GraphicsPipelineCreationError),
+    DescriptorSet(PersistentDescriptorSetError),
+    DescriptorSetBuild(PersistentDescriptorSetBuildError),
+    Acquire(AcquireError),
+    BeginRenderPass(BeginRenderPassError),
+    Draw(DrawError),
+    DrawIndexed(DrawIndexedError),
+    Commands(AutoCommandBufferBuilderContextError),
+    CopyBuffer(CopyBufferError),
+    CopyImage(CopyBufferImageError),
+    BuildCommands(BuildError),
+    Execute(CommandBufferExecError),
+    Flush(FlushError),
+    ReadBack(ReadLockError),
+    Uniforms(WriteLockError),
+    Shader(ShaderError),
+    Heightmap(HeightmapError),
+    Obj(ObjError),
+    Gltf(GltfError),
+    InputMap(InputMapError)
+);
+
+impl From<LoadingError> for VulrustError {
+    fn from(err: LoadingError) -> VulrustError {
+        VulrustError::Instance(err.into())
+    }
+}
+
+impl From<OomError> for VulrustError {
+    fn from(err: OomError) -> VulrustError {
+        VulrustError::OutOfMemory(err.into())
+    }
+}
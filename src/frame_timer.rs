@@ -0,0 +1,54 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+
+// Longest time a single frame may cover, so a stall (dragging the window, a breakpoint)
+// doesn't fling the camera across the map on the next frame.
+const MAX_DELTA: f32 = 0.25;
+
+// Measures the real time between frames and optionally holds frames back to a maximum rate.
+pub struct FrameTimer {
+    last: Instant,
+    // Shortest time between two ticks, `None` runs as fast as presenting allows.
+    pub min_frame_time: Option<Duration>
+}
+
+impl FrameTimer {
+    pub fn new() -> FrameTimer {
+        FrameTimer {
+            last: Instant::now(),
+            min_frame_time: None
+        }
+    }
+
+    // A cap that isn't a positive number, or whose frame time is too long for a duration,
+    // means no cap.
+    pub fn set_fps_cap(&mut self, fps: Option<f32>) {
+        self.min_frame_time = fps
+            .filter(|fps| fps.is_finite() && *fps > 0.0)
+            .and_then(|fps| Duration::try_from_secs_f32(1.0 / fps).ok());
+    }
+
+    // Waits out the frame cap, then returns the seconds since the previous tick.
+    pub fn tick(&mut self) -> f32 {
+        let remaining = self.remaining(Instant::now());
+        if remaining > Duration::from_secs(0) {
+            thread::sleep(remaining);
+        }
+        self.tick_at(Instant::now())
+    }
+
+    pub fn tick_at(&mut self, now: Instant) -> f32 {
+        let elapsed = now.duration_since(self.last);
+        self.last = now;
+        elapsed.as_secs_f32().min(MAX_DELTA)
+    }
+
+    // Time left until the cap lets the next frame start.
+    pub fn remaining(&self, now: Instant) -> Duration {
+        match self.min_frame_time {
+            Some(min_frame_time) => (self.last + min_frame_time).saturating_duration_since(now),
+            None => Duration::from_secs(0)
+        }
+    }
+}
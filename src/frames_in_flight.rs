@@ -0,0 +1,91 @@
+use std::sync::Arc;
+use vulkano::buffer::BufferUsage;
+use vulkano::buffer::cpu_access::CpuAccessibleBuffer;
+use vulkano::device::Device;
+use vulkano::sync::{FenceSignalFuture, GpuFuture};
+
+use error::VulrustError;
+use shader_utils;
+
+
+pub const DEFAULT_FRAMES_IN_FLIGHT: usize = 2;
+
+// Signalled when the GPU is done with a frame. Shared between the frame slot, which waits on
+// it, and the next frame, which is chained after it.
+pub type FrameFence = Arc<FenceSignalFuture<Box<GpuFuture + Send + Sync>>>;
+
+// What one frame in flight owns. It's reused every `FramesInFlight::len` frames, once the GPU
+// is done with the frame it was last used for.
+pub struct FrameSlot {
+    pub world_uniforms: Arc<CpuAccessibleBuffer<shader_utils::vs::ty::UniformMatrices>>,
+    pub fence: Option<FrameFence>
+}
+
+// Lets the CPU record up to `len` frames ahead of the GPU. Each frame gets its own uniform
+// buffers and descriptor sets, so nothing is allocated per frame and a frame only waits
+// for the GPU when it comes back round to a slot that's still in use.
+pub struct FramesInFlight {
+    slots: Vec<FrameSlot>,
+    current: usize
+}
+
+impl FramesInFlight {
+    pub fn new(device: Arc<Device>, count: usize) -> Result<FramesInFlight, VulrustError> {
+        let slots = (0 .. check_count(count)?).map(|_| {
+            Ok(FrameSlot {
+                world_uniforms: world_uniforms_buffer(device.clone())?,
+                fence: None
+            })
+        }).collect::<Result<Vec<_>, VulrustError>>()?;
+
+        Ok(FramesInFlight {
+            // The first `begin_frame` moves on to slot 0.
+            current: slots.len() - 1,
+            slots: slots
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    // Moves on to the next slot and waits until the GPU is done with its last frame.
+    // Returns the index of the slot, which is the `frame` of `FrameUniforms`.
+    pub fn begin_frame(&mut self) -> Result<usize, VulrustError> {
+        self.current = (self.current + 1) % self.slots.len();
+        if let Some(fence) = self.slots[self.current].fence.take() {
+            fence.wait(None)?;
+        }
+        Ok(self.current)
+    }
+
+    pub fn current(&self) -> &FrameSlot {
+        &self.slots[self.current]
+    }
+
+    // The fence of the frame just submitted with the current slot, `None` if it never got
+    // submitted.
+    pub fn end_frame(&mut self, fence: Option<FrameFence>) {
+        self.slots[self.current].fence = fence;
+    }
+}
+
+// At least one frame has to be in flight.
+pub fn check_count(count: usize) -> Result<usize, VulrustError> {
+    if count == 0 {
+        return Err(VulrustError::invalid_argument("frames in flight", &count.to_string()));
+    }
+    Ok(count)
+}
+
+// One `UniformMatrices` buffer, written by `FrameUniforms::new`.
+pub fn world_uniforms_buffer(device: Arc<Device>) -> Result<Arc<CpuAccessibleBuffer<shader_utils::vs::ty::UniformMatrices>>, VulrustError> {
+    let matrices = shader_utils::vs::ty::UniformMatrices {
+        view_projection: [[0.0; 4]; 4]
+    };
+    Ok(CpuAccessibleBuffer::from_data(device, BufferUsage::uniform_buffer(), matrices)?)
+}
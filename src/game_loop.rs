@@ -0,0 +1,108 @@
+use error::VulrustError;
+use frame_timer::FrameTimer;
+
+
+// Most fixed updates run to catch up within one frame. A machine that can't keep up slows
+// the simulation down instead of falling further behind with every frame.
+const MAX_TICKS_PER_FRAME: u32 = 5;
+
+// What a frame gets to know about the simulation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+    // Real seconds since the previous frame.
+    pub dt: f32,
+    // Fixed updates run before this frame.
+    pub ticks: u32,
+    // Share of a tick that is simulated time not yet covered by an update, 0 to 1.
+    pub alpha: f32
+}
+
+// Turns real frame times into a whole number of fixed-length ticks.
+pub struct FixedStep {
+    // Seconds per tick.
+    pub tick: f32,
+    pub max_ticks: u32,
+    accumulator: f32
+}
+
+// Ticks per second have to be a positive, finite number.
+pub fn is_valid_rate(rate: f32) -> bool {
+    rate.is_finite() && rate > 0.0
+}
+
+impl FixedStep {
+    pub fn new(rate: f32) -> Result<FixedStep, VulrustError> {
+        if !is_valid_rate(rate) {
+            return Err(VulrustError::invalid_argument("tick rate", &rate.to_string()));
+        }
+        Ok(FixedStep {
+            tick: 1.0 / rate,
+            max_ticks: MAX_TICKS_PER_FRAME,
+            accumulator: 0.0
+        })
+    }
+
+    // Adds the frame time and returns how many ticks are due. Time beyond `max_ticks` is
+    // dropped, keeping only the part of a tick that was already under way.
+    pub fn advance(&mut self, dt: f32) -> u32 {
+        self.accumulator += dt;
+        let mut ticks = 0;
+        while self.accumulator >= self.tick {
+            self.accumulator -= self.tick;
+            ticks += 1;
+            if ticks == self.max_ticks {
+                self.accumulator %= self.tick;
+                break;
+            }
+        }
+        ticks
+    }
+
+    pub fn alpha(&self) -> f32 {
+        self.accumulator / self.tick
+    }
+}
+
+// Runs fixed-rate updates and renders frames in between as fast as the timer allows.
+pub struct GameLoop {
+    pub timer: FrameTimer,
+    pub step: FixedStep
+}
+
+impl GameLoop {
+    pub fn new(rate: f32) -> Result<GameLoop, VulrustError> {
+        Ok(GameLoop {
+            timer: FrameTimer::new(),
+            step: FixedStep::new(rate)?
+        })
+    }
+
+    // Calls `update` with the tick length as often as the elapsed time asks for, then `frame`,
+    // until `frame` returns false. Both work on `state`.
+    pub fn run<S, U, F>(&mut self, state: &mut S, mut update: U, mut frame: F)
+        where U: FnMut(&mut S, f32), F: FnMut(&mut S, Frame) -> bool
+    {
+        loop {
+            let dt = self.timer.tick();
+            let next = self.advance(state, dt, &mut update);
+            if !frame(state, next) {
+                break;
+            }
+        }
+    }
+
+    // One round of `run` with a given frame time.
+    pub fn advance<S, U>(&mut self, state: &mut S, dt: f32, update: &mut U) -> Frame
+        where U: FnMut(&mut S, f32)
+    {
+        let ticks = self.step.advance(dt);
+        for _ in 0 .. ticks {
+            update(state, self.step.tick);
+        }
+        Frame {
+            dt: dt,
+            ticks: ticks,
+            alpha: self.step.alpha()
+        }
+    }
+}
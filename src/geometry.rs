@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::mem;
+
+use vertex_types::{Vertex3DNormal3D, Vertex3DUV};
+
+
+// Triangles over shared vertices, drawn with `draw_indexed`.
+#[derive(Debug, Clone)]
+pub struct IndexedMesh<V> {
+    pub vertices: Vec<V>,
+    pub indices: Vec<u32>
+}
+
+impl<V> IndexedMesh<V> {
+    // Wraps a plain triangle list without sharing anything, for sources that come de-indexed (OBJ).
+    pub fn from_triangle_list(vertices: Vec<V>) -> IndexedMesh<V> {
+        let indices = (0 .. vertices.len() as u32).collect();
+        IndexedMesh { vertices: vertices, indices: indices }
+    }
+
+    pub fn vertex_bytes(&self) -> usize {
+        self.vertices.len() * mem::size_of::<V>()
+    }
+
+    pub fn index_bytes(&self) -> usize {
+        self.indices.len() * mem::size_of::<u32>()
+    }
+
+    // What the same triangles take as a plain triangle list, one vertex per index.
+    pub fn triangle_list_bytes(&self) -> usize {
+        self.indices.len() * mem::size_of::<V>()
+    }
+
+    pub fn print_memory_usage(&self, name: &str) {
+        println!(
+            "{}: {} vertices, {} indices, {:.1} KiB indexed (was {:.1} KiB as a triangle list).",
+            name,
+            self.vertices.len(),
+            self.indices.len(),
+            (self.vertex_bytes() + self.index_bytes()) as f32 / 1024.0,
+            self.triangle_list_bytes() as f32 / 1024.0
+        );
+    }
+}
+
+// Unit cube from (0, 0, 0) to (1, 1, 1), four vertices per face so every face keeps its own normal.
+pub fn cube() -> IndexedMesh<Vertex3DNormal3D> {
+    let positions: Vec<[f32; 3]> = vec![
+        [0.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0],
+        [0.0, 1.0, 0.0],
+        [0.0, 1.0, 1.0],
+        [1.0, 0.0, 0.0],
+        [1.0, 0.0, 1.0],
+        [1.0, 1.0, 0.0],
+        [1.0, 1.0, 1.0]
+    ];
+
+    let normals: Vec<[f32; 3]> = vec![
+        [ 0.0,  0.0,  1.0],
+        [ 0.0,  0.0, -1.0],
+        [ 0.0,  1.0,  0.0],
+        [ 0.0, -1.0,  0.0],
+        [ 1.0,  0.0,  0.0],
+        [-1.0,  0.0,  0.0]
+    ];
+
+    let faces: Vec<(u32, u32)> = vec![
+        (1, 2), (7, 2), (5, 2),
+        (1, 2), (3, 2), (7, 2),
+        (1, 6), (4, 6), (3, 6),
+        (1, 6), (2, 6), (4, 6),
+        (3, 3), (8, 3), (7, 3),
+        (3, 3), (4, 3), (8, 3),
+        (5, 5), (7, 5), (8, 5),
+        (5, 5), (8, 5), (6, 5),
+        (1, 4), (5, 4), (6, 4),
+        (1, 4), (6, 4), (2, 4),
+        (2, 1), (6, 1), (8, 1),
+        (2, 1), (8, 1), (4, 1)
+    ];
+
+    let mut mesh = IndexedMesh { vertices: Vec::new(), indices: Vec::new() };
+    let mut shared: HashMap<(u32, u32), u32> = HashMap::new();
+    for &face in faces.iter() {
+        let vertices = &mut mesh.vertices;
+        let index = *shared.entry(face).or_insert_with(|| {
+            let vi: u32 = face.0 - 1;
+            let ni: u32 = face.1 - 1;
+            vertices.push(Vertex3DNormal3D {
+                position: positions[vi as usize],
+                normal: normals[ni as usize]
+            });
+            vertices.len() as u32 - 1
+        });
+        mesh.indices.push(index);
+    }
+
+    mesh
+}
+
+// Flat grid of `dim + 1` by `dim + 1` quads of size `s` going along +x and -z,
+// with UVs running from 0 to 1 over the first `dim` quads.
+pub fn plane_square(dim: u32, s: f32) -> IndexedMesh<Vertex3DUV> {
+    let side = dim + 2;
+    let mut mesh = IndexedMesh {
+        vertices: Vec::with_capacity((side * side) as usize),
+        indices: Vec::with_capacity((6 * (dim + 1) * (dim + 1)) as usize)
+    };
+
+    for i in 0 .. side {
+        for j in 0 .. side {
+            mesh.vertices.push(Vertex3DUV {
+                position: [s * i as f32, 0.0, -s * j as f32],
+                uv: [(i as f32) / (dim as f32), (j as f32) / (dim as f32)]
+            });
+        }
+    }
+
+    for i in 0 .. dim + 1 {
+        for j in 0 .. dim + 1 {
+            let left_bottom = i * side + j;
+            let left_top = left_bottom + 1;
+            let right_bottom = left_bottom + side;
+            let right_top = right_bottom + 1;
+
+            mesh.indices.extend_from_slice(&[
+                left_bottom, left_top, right_bottom,
+                left_top, right_bottom, right_top
+            ]);
+        }
+    }
+
+    mesh
+}
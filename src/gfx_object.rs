@@ -1,307 +1,628 @@
 use std::sync::Arc;
+use cgmath::Matrix4;
+use cgmath::SquareMatrix;
 use vulkano::buffer::BufferUsage;
+use vulkano::buffer::immutable::ImmutableBuffer;
 use vulkano::buffer::cpu_access::CpuAccessibleBuffer;
+use vulkano::command_buffer::AutoCommandBufferBuilder;
+use vulkano::command_buffer::DynamicState;
+use vulkano::descriptor::DescriptorSet;
 use vulkano::descriptor::PipelineLayoutAbstract;
+use vulkano::descriptor::descriptor_set::PersistentDescriptorSet;
 use vulkano::device::Device;
+use vulkano::format::R8G8B8A8Srgb;
+use vulkano::format::R16Unorm;
 use vulkano::framebuffer::RenderPassAbstract;
 use vulkano::framebuffer::Subpass;
+use vulkano::image::immutable::ImmutableImage;
 use vulkano::pipeline::GraphicsPipeline;
+use vulkano::pipeline::GraphicsPipelineCreationError;
+use vulkano::pipeline::shader::ShaderModule;
+use vulkano::pipeline::vertex::Vertex;
 use vulkano::pipeline::vertex::OneVertexOneInstanceDefinition;
 use vulkano::pipeline::vertex::SingleBufferDefinition;
-
+use vulkano::sampler::Sampler;
+use vulkano::sync::GpuFuture;
+
+use drawable::{Drawable, FrameCache, FrameUniforms};
+use error::VulrustError;
+use geometry;
+use geometry::IndexedMesh;
+use obj_loader::ObjMesh;
+use rectangle_instance::RectangleInstance;
+use shader_interface;
+use shader_interface::InterfaceError;
+use shader_reload;
+use shader_reload::{ShaderError, ShaderLibrary, ShaderStage};
 use shader_utils;
-use vertex_types::{Vertex3D, Vertex3DColor3D, Vertex3DNormal3D, Vertex3DUV};
+use upload::Uploads;
+use vertex_types::{Vertex3D, Vertex3DColor3D, Vertex3DNormal3D, Vertex3DNormal3DUV, Vertex3DUV};
 
 
 type SBuffer = OneVertexOneInstanceDefinition<Vertex3D, Vertex3DColor3D>;
 type DOBuffer = SingleBufferDefinition<Vertex3DNormal3D>;
 type UVBuffer = SingleBufferDefinition<Vertex3DUV>;
+type MeshBuffer = SingleBufferDefinition<Vertex3DNormal3DUV>;
 type BPipeline = Box<PipelineLayoutAbstract + Send + Sync>;
 type RPass = Arc<RenderPassAbstract + Send + Sync>;
+type DSet = Arc<DescriptorSet + Send + Sync>;
+// Sets 0 and 1 of a frame in flight, with the spin delta buffer behind set 1.
+type DeltaSets = (DSet, DSet, Arc<CpuAccessibleBuffer<shader_utils::vs::ty::DeltaUniform>>);
+// How an object puts its shaders into a pipeline, and whether a loaded shader fits there.
+type BuildPipeline<X> = fn(&Arc<Device>, &Subpass<RPass>, &ShaderModule, &ShaderModule) -> Result<Arc<GraphicsPipeline<X, BPipeline, RPass>>, GraphicsPipelineCreationError>;
+type CheckInterface = fn(ShaderStage, &[u32]) -> Result<(), InterfaceError>;
+
+// The shaders an object is drawn with, the pipeline built from them and the descriptor sets
+// built for that pipeline. Only `build_pipeline` and `check_interface` differ between objects,
+// reloading is the same for all of them.
+pub struct ShaderPipeline<X, S> {
+    // Base name of the shader files in the library, e.g. "cube" for `cube.vert`.
+    pub name: &'static str,
+    pub device: Arc<Device>,
+    subpass: Subpass<RPass>,
+    pub vertex_shader: Arc<ShaderModule>,
+    pub fragment_shader: Arc<ShaderModule>,
+    pub pipeline: Option<Arc<GraphicsPipeline<X, BPipeline, RPass>>>,
+    pub frame_sets: FrameCache<S>,
+    build_pipeline: BuildPipeline<X>,
+    check_interface: CheckInterface
+}
+
+impl<X, S: Clone> ShaderPipeline<X, S> {
+    pub fn new(name: &'static str, device: Arc<Device>, render_pass: RPass, vertex_shader: Arc<ShaderModule>, fragment_shader: Arc<ShaderModule>,
+        build_pipeline: BuildPipeline<X>, check_interface: CheckInterface) -> Result<ShaderPipeline<X, S>, VulrustError>
+    {
+        let subpass = Subpass::from(render_pass, 0).ok_or(VulrustError::Missing("subpass 0 of the render pass"))?;
+        Ok(ShaderPipeline {
+            name: name,
+            device: device,
+            subpass: subpass,
+            vertex_shader: vertex_shader,
+            fragment_shader: fragment_shader,
+            pipeline: None,
+            frame_sets: FrameCache::new(),
+            build_pipeline: build_pipeline,
+            check_interface: check_interface
+        })
+    }
+
+    // Builds the pipeline with the current shaders.
+    pub fn rebuild(&mut self) -> Result<(), GraphicsPipelineCreationError> {
+        let pipeline = (self.build_pipeline)(&self.device, &self.subpass, &self.vertex_shader, &self.fragment_shader)?;
+        self.pipeline = Some(pipeline);
+        self.frame_sets.clear();
+        Ok(())
+    }
+
+    // Swaps in whatever shaders `library` has under `name`. On error everything stays as it was.
+    pub fn reload(&mut self, library: &ShaderLibrary) -> Result<(), ShaderError> {
+        if let Some((vertex_shader, fragment_shader)) = library.modules(self.name, self.device.clone(), &self.vertex_shader, &self.fragment_shader, self.check_interface)? {
+            let pipeline = (self.build_pipeline)(&self.device, &self.subpass, &vertex_shader, &fragment_shader).map_err(|err| ShaderError::Pipeline(self.name.to_string(), err))?;
+            self.pipeline = Some(pipeline);
+            self.frame_sets.clear();
+            self.vertex_shader = vertex_shader;
+            self.fragment_shader = fragment_shader;
+        }
+        Ok(())
+    }
+
+    pub fn get_pipeline(&self) -> Result<Arc<GraphicsPipeline<X, BPipeline, RPass>>, VulrustError>
+    {
+        match self.pipeline {
+            Some(ref pipeline) => Ok(pipeline.clone()),
+            None => Err(VulrustError::Missing("pipeline"))
+        }
+    }
+}
+
+// A `ShaderPipeline` of the compiled-in `$vs` and `$fs` of `shader_utils`. Reloaded shaders are
+// checked against their `shader!` types, with `$member` looking up the vertex fields the
+// pipeline feeds the vertex shader from.
+macro_rules! shader_pipeline {
+    ($name:expr, $device:expr, $render_pass:expr, $vs:ident, $fs:ident, $member:expr, $build_pipeline:expr) => {
+        ShaderPipeline::new(
+            $name,
+            $device.clone(),
+            $render_pass.clone(),
+            shader_utils::$vs::Shader::load($device.clone())?.module().clone(),
+            shader_utils::$fs::Shader::load($device.clone())?.module().clone(),
+            $build_pipeline,
+            |stage, spirv| match stage {
+                ShaderStage::Vertex => shader_interface::check_vertex_shader(spirv, &shader_utils::$vs::MainInput, &shader_utils::$vs::MainOutput, &shader_utils::$vs::Layout(shader_reload::vertex_stage()), $member),
+                ShaderStage::Fragment => shader_interface::check_fragment_shader(spirv, &shader_utils::$fs::MainInput, &shader_utils::$fs::MainOutput, &shader_utils::$fs::Layout(shader_reload::fragment_stage()))
+            }
+        )
+    }
+}
 
 pub struct GfxObject {
     pub device: Arc<Device>,
+    pub uploads: Uploads,
     pub render_pass: RPass,
-    pub vertex_buffer: Option<Arc<CpuAccessibleBuffer<[Vertex3D]>>>,
-    pub pipeline: Option<Arc<GraphicsPipeline<SBuffer, BPipeline, RPass>>>
+    pub vertex_buffer: Option<Arc<ImmutableBuffer<[Vertex3D]>>>,
+    pub instances_buffer: Option<Arc<ImmutableBuffer<[Vertex3DColor3D]>>>,
+    pub shaders: ShaderPipeline<SBuffer, DeltaSets>
 }
 
 impl GfxObject {
-    pub fn new(device: Arc<Device>, render_pass: RPass) -> GfxObject {
-        GfxObject {
-            device: device,
+    pub fn new(device: Arc<Device>, uploads: Uploads, render_pass: RPass) -> Result<GfxObject, VulrustError> {
+        let shaders = shader_pipeline!("rectangles", device, render_pass, vs, fs, |name| Vertex3D::member(name).or_else(|| Vertex3DColor3D::member(name)), GfxObject::build_pipeline)?;
+        Ok(GfxObject {
+            device: device.clone(),
+            uploads: uploads,
             render_pass: render_pass,
             vertex_buffer: None,
-            pipeline: None
-        }
+            instances_buffer: None,
+            shaders: shaders
+        })
     }
 
-    pub fn create_rectangle(&mut self, w: f32, h: f32)
+    pub fn create_rectangle(&mut self, w: f32, h: f32) -> Result<Box<GpuFuture + Send + Sync>, VulrustError>
     {
-        let vertex_buffer = CpuAccessibleBuffer::from_iter(
-            self.device.clone(),
-            BufferUsage::all(),
+        let (vertex_buffer, vertex_future) = self.uploads.buffer(
             vec![
                 Vertex3D { position: [0.0, 0.0, 0.0] },
                 Vertex3D { position: [0.0, h, 0.0] },
                 Vertex3D { position: [w, 0.0, 0.0] },
                 Vertex3D { position: [w, h, 0.0] },
-            ].into_iter()
-        ).unwrap();
+            ].into_iter(),
+            BufferUsage::vertex_buffer()
+        )?;
 
         self.vertex_buffer = Some(vertex_buffer);
 
-        let vs = shader_utils::vs::Shader::load(self.device.clone()).expect("failed to create shader module");
-        let fs = shader_utils::fs::Shader::load(self.device.clone()).expect("failed to create shader module");
+        self.shaders.rebuild()?;
+        Ok(vertex_future)
+    }
+
+    pub fn set_instances(&mut self, instances: &[RectangleInstance]) -> Result<Box<GpuFuture + Send + Sync>, VulrustError>
+    {
+        let instance_vertices = instances.iter().map(|ri| {
+            ri.get_instance_vertex()
+        }).collect::<Result<Vec<_>, _>>()?;
+        let (instances_buffer, instances_future) = self.uploads.buffer(
+            instance_vertices.into_iter(),
+            BufferUsage::vertex_buffer()
+        )?;
+
+        self.instances_buffer = Some(instances_buffer);
+        Ok(instances_future)
+    }
+
+    fn build_pipeline(device: &Arc<Device>, subpass: &Subpass<RPass>, vertex_shader: &ShaderModule, fragment_shader: &ShaderModule) -> Result<Arc<GraphicsPipeline<SBuffer, BPipeline, RPass>>, GraphicsPipelineCreationError>
+    {
+        let vs = unsafe { shader_reload::vertex_entry_point(vertex_shader, shader_utils::vs::MainInput, shader_utils::vs::MainOutput, shader_utils::vs::Layout(shader_reload::vertex_stage())) };
+        let fs = unsafe { shader_reload::fragment_entry_point(fragment_shader, shader_utils::fs::MainInput, shader_utils::fs::MainOutput, shader_utils::fs::Layout(shader_reload::fragment_stage())) };
 
-        let subpass = Subpass::from(self.render_pass.clone(), 0).expect("render pass failed");
-        let pipeline: Arc<GraphicsPipeline<SBuffer, BPipeline, RPass>> = Arc::new(GraphicsPipeline::start()
+        let pipeline = GraphicsPipeline::start()
             .vertex_input(OneVertexOneInstanceDefinition::<Vertex3D, Vertex3DColor3D>::new())
-            .vertex_shader(vs.main_entry_point(), ())
+            .vertex_shader(vs, ())
             .triangle_strip()
             .viewports_dynamic_scissors_irrelevant(1)
-            .fragment_shader(fs.main_entry_point(), ())
+            .fragment_shader(fs, ())
             .depth_stencil_simple_depth()
-            .render_pass(subpass)
-            .build(self.device.clone())
-            .expect("render pass failed")
-        );
-
-        self.pipeline = Some(pipeline);
+            .render_pass(subpass.clone())
+            .build(device.clone())?;
+        Ok(Arc::new(pipeline))
     }
 
-    pub fn get_pipeline(&self) -> Arc<GraphicsPipeline<SBuffer, BPipeline, RPass>>
+    pub fn get_vertex_buffer(&self) -> Result<Arc<ImmutableBuffer<[Vertex3D]>>, VulrustError>
     {
-        match self.pipeline {
-            Some(ref pipeline) => { pipeline.clone() }
-            None => { panic!("Empty pipeline!") }
+        match self.vertex_buffer {
+            Some(ref vertex_buffer) => Ok(vertex_buffer.clone()),
+            None => Err(VulrustError::Missing("vertex buffer"))
         }
     }
 
-    pub fn get_vertex_buffer(&self) -> Arc<CpuAccessibleBuffer<[Vertex3D]>>
+    pub fn get_instances_buffer(&self) -> Result<Arc<ImmutableBuffer<[Vertex3DColor3D]>>, VulrustError>
     {
-        match self.vertex_buffer {
-            Some(ref vertex_buffer) => { vertex_buffer.clone() }
-            None => { panic!("Empty vertex buffer!") }
+        match self.instances_buffer {
+            Some(ref instances_buffer) => Ok(instances_buffer.clone()),
+            None => Err(VulrustError::Missing("instances buffer"))
         }
     }
 }
 
+impl Drawable for GfxObject {
+    fn draw(&self, command_buffer_builder: AutoCommandBufferBuilder, dynamic_state: &DynamicState, uniforms: &FrameUniforms) -> Result<AutoCommandBufferBuilder, VulrustError> {
+        let (world_uniforms_descriptor, delta_descriptor_set, delta_buffer) = self.shaders.frame_sets.get_or_build(uniforms.frame, || {
+            let world_uniforms_descriptor = Arc::new(
+                PersistentDescriptorSet::start(self.shaders.get_pipeline()?, 0)
+
+                .add_buffer(uniforms.world.clone())?
+
+                .build()?
+            );
+
+            let delta_buffer = CpuAccessibleBuffer::from_data(self.device.clone(), BufferUsage::uniform_buffer(), shader_utils::vs::ty::DeltaUniform { delta: 0.0 })?;
+            let delta_descriptor_set = Arc::new(
+                PersistentDescriptorSet::start(self.shaders.get_pipeline()?, 1)
+
+                .add_buffer(delta_buffer.clone())?
+
+                .build()?
+            );
+            Ok((world_uniforms_descriptor as DSet, delta_descriptor_set as DSet, delta_buffer))
+        })?;
+        *delta_buffer.write()? = shader_utils::vs::ty::DeltaUniform {
+            delta: (uniforms.delta % 630.0) / 100.0
+        };
+
+        Ok(command_buffer_builder.draw(
+            self.shaders.get_pipeline()?,
+            dynamic_state,
+            (self.get_vertex_buffer()?, self.get_instances_buffer()?),
+            (world_uniforms_descriptor, delta_descriptor_set),
+            ()
+        )?)
+    }
+
+    fn reload_shaders(&mut self, library: &ShaderLibrary) -> Result<(), ShaderError> {
+        self.shaders.reload(library)
+    }
+}
+
 pub struct GfxObject3D {
     pub device: Arc<Device>,
+    pub uploads: Uploads,
     pub render_pass: RPass,
-    pub vertex_buffer: Option<Arc<CpuAccessibleBuffer<[Vertex3DNormal3D]>>>,
-    pub pipeline: Option<Arc<GraphicsPipeline<DOBuffer, BPipeline, RPass>>>
+    pub vertex_buffer: Option<Arc<ImmutableBuffer<[Vertex3DNormal3D]>>>,
+    pub index_buffer: Option<Arc<ImmutableBuffer<[u32]>>>,
+    pub shaders: ShaderPipeline<DOBuffer, DSet>
 }
 
 impl GfxObject3D {
-    pub fn new(device: Arc<Device>, render_pass: RPass) -> GfxObject3D {
-        GfxObject3D {
-            device: device,
+    pub fn new(device: Arc<Device>, uploads: Uploads, render_pass: RPass) -> Result<GfxObject3D, VulrustError> {
+        let shaders = shader_pipeline!("cube", device, render_pass, vs_cube, fs_cube, Vertex3DNormal3D::member, GfxObject3D::build_pipeline)?;
+        Ok(GfxObject3D {
+            device: device.clone(),
+            uploads: uploads,
             render_pass: render_pass,
             vertex_buffer: None,
-            pipeline: None
-        }
+            index_buffer: None,
+            shaders: shaders
+        })
     }
 
-    pub fn create_cube(&mut self)
+    pub fn create_cube(&mut self) -> Result<Box<GpuFuture + Send + Sync>, VulrustError>
     {
-        let _vertices: Vec<[f32; 3]> = vec![
-            [0.0, 0.0, 0.0],
-            [0.0, 0.0, 1.0],
-            [0.0, 1.0, 0.0],
-            [0.0, 1.0, 1.0],
-            [1.0, 0.0, 0.0],
-            [1.0, 0.0, 1.0],
-            [1.0, 1.0, 0.0],
-            [1.0, 1.0, 1.0]
-        ];
-
-        let _normals: Vec<[f32; 3]> = vec![
-            [ 0.0,  0.0,  1.0],
-            [ 0.0,  0.0, -1.0],
-            [ 0.0,  1.0,  0.0],
-            [ 0.0, -1.0,  0.0],
-            [ 1.0,  0.0,  0.0],
-            [-1.0,  0.0,  0.0]
-        ];
-
-        let _faces: Vec<(u32, u32)> = vec![
-            (1, 2), (7, 2), (5, 2),
-            (1, 2), (3, 2), (7, 2),
-            (1, 6), (4, 6), (3, 6),
-            (1, 6), (2, 6), (4, 6),
-            (3, 3), (8, 3), (7, 3),
-            (3, 3), (4, 3), (8, 3),
-            (5, 5), (7, 5), (8, 5),
-            (5, 5), (8, 5), (6, 5),
-            (1, 4), (5, 4), (6, 4),
-            (1, 4), (6, 4), (2, 4),
-            (2, 1), (6, 1), (8, 1),
-            (2, 1), (8, 1), (4, 1)
-        ];
-
-        let _data: Vec<Vertex3DNormal3D> = _faces.iter().map(|&_face| {
-            let vi: u32 = _face.0 - 1;
-            let ni: u32 = _face.1 - 1;
-            Vertex3DNormal3D {
-                position: _vertices[vi as usize].clone(),
-                normal: _normals[ni as usize].clone()
-            }
-        }).collect();
+        let mesh = geometry::cube();
+        mesh.print_memory_usage("Cube");
+
+        self.create_from_mesh(mesh)
+    }
+
+    pub fn create_from_obj_mesh(&mut self, mesh: &ObjMesh) -> Result<Box<GpuFuture + Send + Sync>, VulrustError>
+    {
+        self.create_from_mesh(IndexedMesh::from_triangle_list(mesh.to_vertex3d_normal3d()))
+    }
 
-        let vertex_buffer = CpuAccessibleBuffer::from_iter(
-            self.device.clone(),
-            BufferUsage::all(),
-            _data.into_iter()
-        ).unwrap();
+    pub fn create_from_mesh(&mut self, mesh: IndexedMesh<Vertex3DNormal3D>) -> Result<Box<GpuFuture + Send + Sync>, VulrustError>
+    {
+        let (vertex_buffer, vertex_future) = self.uploads.buffer(mesh.vertices.into_iter(), BufferUsage::vertex_buffer())?;
+        let (index_buffer, index_future) = self.uploads.buffer(mesh.indices.into_iter(), BufferUsage::index_buffer())?;
 
         self.vertex_buffer = Some(vertex_buffer);
+        self.index_buffer = Some(index_buffer);
+
+        self.shaders.rebuild()?;
+        Ok(Box::new(vertex_future.join(index_future)) as Box<GpuFuture + Send + Sync>)
+    }
 
-        let vs = shader_utils::vs_cube::Shader::load(self.device.clone()).expect("failed to create shader module");
-        let fs = shader_utils::fs_cube::Shader::load(self.device.clone()).expect("failed to create shader module");
+    fn build_pipeline(device: &Arc<Device>, subpass: &Subpass<RPass>, vertex_shader: &ShaderModule, fragment_shader: &ShaderModule) -> Result<Arc<GraphicsPipeline<DOBuffer, BPipeline, RPass>>, GraphicsPipelineCreationError>
+    {
+        let vs = unsafe { shader_reload::vertex_entry_point(vertex_shader, shader_utils::vs_cube::MainInput, shader_utils::vs_cube::MainOutput, shader_utils::vs_cube::Layout(shader_reload::vertex_stage())) };
+        let fs = unsafe { shader_reload::fragment_entry_point(fragment_shader, shader_utils::fs_cube::MainInput, shader_utils::fs_cube::MainOutput, shader_utils::fs_cube::Layout(shader_reload::fragment_stage())) };
 
-        let subpass = Subpass::from(self.render_pass.clone(), 0).expect("render pass failed");
-        let pipeline: Arc<GraphicsPipeline<DOBuffer, BPipeline, RPass>> = Arc::new(GraphicsPipeline::start()
+        let pipeline = GraphicsPipeline::start()
             .vertex_input(SingleBufferDefinition::<Vertex3DNormal3D>::new())
-            .vertex_shader(vs.main_entry_point(), ())
+            .vertex_shader(vs, ())
             .triangle_list()
             .viewports_dynamic_scissors_irrelevant(1)
-            .fragment_shader(fs.main_entry_point(), ())
+            .fragment_shader(fs, ())
             .depth_stencil_simple_depth()
-            .render_pass(subpass)
-            .build(self.device.clone())
-            .expect("render pass failed")
-        );
-
-        self.pipeline = Some(pipeline);
+            .render_pass(subpass.clone())
+            .build(device.clone())?;
+        Ok(Arc::new(pipeline))
     }
 
-    pub fn get_pipeline(&self) -> Arc<GraphicsPipeline<DOBuffer, BPipeline, RPass>>
+    pub fn get_vertex_buffer(&self) -> Result<Arc<ImmutableBuffer<[Vertex3DNormal3D]>>, VulrustError>
     {
-        match self.pipeline {
-            Some(ref pipeline) => { pipeline.clone() }
-            None => { panic!("Empty pipeline!") }
+        match self.vertex_buffer {
+            Some(ref vertex_buffer) => Ok(vertex_buffer.clone()),
+            None => Err(VulrustError::Missing("vertex buffer"))
         }
     }
 
-    pub fn get_vertex_buffer(&self) -> Arc<CpuAccessibleBuffer<[Vertex3DNormal3D]>>
+    pub fn get_index_buffer(&self) -> Result<Arc<ImmutableBuffer<[u32]>>, VulrustError>
     {
-        match self.vertex_buffer {
-            Some(ref vertex_buffer) => { vertex_buffer.clone() }
-            None => { panic!("Empty vertex buffer!") }
+        match self.index_buffer {
+            Some(ref index_buffer) => Ok(index_buffer.clone()),
+            None => Err(VulrustError::Missing("index buffer"))
         }
     }
 }
 
+impl Drawable for GfxObject3D {
+    fn draw(&self, command_buffer_builder: AutoCommandBufferBuilder, dynamic_state: &DynamicState, uniforms: &FrameUniforms) -> Result<AutoCommandBufferBuilder, VulrustError> {
+        let world_uniforms_descriptor = self.shaders.frame_sets.get_or_build(uniforms.frame, || {
+            Ok(Arc::new(
+                PersistentDescriptorSet::start(self.shaders.get_pipeline()?, 0)
+
+                .add_buffer(uniforms.world.clone())?
+
+                .build()?
+            ) as DSet)
+        })?;
+
+        Ok(command_buffer_builder.draw_indexed(
+            self.shaders.get_pipeline()?,
+            dynamic_state,
+            self.get_vertex_buffer()?,
+            self.get_index_buffer()?,
+            world_uniforms_descriptor,
+            ()
+        )?)
+    }
+
+    fn reload_shaders(&mut self, library: &ShaderLibrary) -> Result<(), ShaderError> {
+        self.shaders.reload(library)
+    }
+}
+
 pub struct GfxObjectHMap {
     pub device: Arc<Device>,
+    pub uploads: Uploads,
     pub render_pass: RPass,
-    pub vertex_buffer: Option<Arc<CpuAccessibleBuffer<[Vertex3DUV]>>>,
-    pub pipeline: Option<Arc<GraphicsPipeline<UVBuffer, BPipeline, RPass>>>
+    pub vertex_buffer: Option<Arc<ImmutableBuffer<[Vertex3DUV]>>>,
+    pub index_buffer: Option<Arc<ImmutableBuffer<[u32]>>>,
+    pub shaders: ShaderPipeline<UVBuffer, DSet>,
+    pub height_map_descriptor: Option<DSet>,
+    pub height_scale: f32,
+    pub offset: [f32; 3]
 }
 
 impl GfxObjectHMap {
-    pub fn new(device: Arc<Device>, render_pass: RPass) -> GfxObjectHMap {
-        GfxObjectHMap {
-            device: device,
+    pub fn new(device: Arc<Device>, uploads: Uploads, render_pass: RPass) -> Result<GfxObjectHMap, VulrustError> {
+        let shaders = shader_pipeline!("terrain", device, render_pass, vs_plane_hmap, fs_plane_hmap, Vertex3DUV::member, GfxObjectHMap::build_pipeline)?;
+        Ok(GfxObjectHMap {
+            device: device.clone(),
+            uploads: uploads,
             render_pass: render_pass,
             vertex_buffer: None,
-            pipeline: None
-        }
+            index_buffer: None,
+            shaders: shaders,
+            height_map_descriptor: None,
+            height_scale: 5.0,
+            offset: [0.0, 0.0, 0.0]
+        })
     }
 
-    pub fn create_plane_square(&mut self, dim: u32, s: f32)
+    pub fn create_plane_square(&mut self, dim: u32, s: f32) -> Result<Box<GpuFuture + Send + Sync>, VulrustError>
     {
-        let mut _data: Vec<Vertex3DUV> = Vec::new();
-
-        let uv_s = 1.0 / (dim as f32);
-        (0 .. dim + 1).for_each(|i| {
-            (0 .. dim + 1).for_each(|j| {
-                let x = s * i as f32;
-                let z = -s * j as f32;
-
-                let left_bottom_vertex  = [    x, 0.0,     z];
-                let left_top_vertex     = [    x, 0.0, z - s];
-                let right_bottom_vertex = [x + s, 0.0,     z];
-                let right_top_vertex    = [x + s, 0.0, z - s];
-
-                let u = (i as f32) / (dim as f32);
-                let v = (j as f32) / (dim as f32);
-
-                let left_bottom_uv =  [       u,        v];
-                let left_top_uv    =  [       u, v + uv_s];
-                let right_bottom_uv = [u + uv_s,        v];
-                let right_top_uv =    [u + uv_s, v + uv_s];
-
-                _data.push(Vertex3DUV {
-                    position: left_bottom_vertex,
-                    uv: left_bottom_uv
-                });
-                _data.push(Vertex3DUV {
-                    position: left_top_vertex,
-                    uv: left_top_uv
-                });
-                _data.push(Vertex3DUV {
-                    position: right_bottom_vertex,
-                    uv: right_bottom_uv
-                });
-
-                _data.push(Vertex3DUV {
-                    position: left_top_vertex,
-                    uv: left_top_uv
-                });
-                _data.push(Vertex3DUV {
-                    position: right_bottom_vertex,
-                    uv: right_bottom_uv
-                });
-                _data.push(Vertex3DUV {
-                    position: right_top_vertex,
-                    uv: right_top_uv
-                });
-            })
-        });
-
-        let vertex_buffer = CpuAccessibleBuffer::from_iter(
-            self.device.clone(),
-            BufferUsage::all(),
-            _data.into_iter()
-        ).unwrap();
+        let mesh = geometry::plane_square(dim, s);
+        mesh.print_memory_usage("Terrain");
+
+        let (vertex_buffer, vertex_future) = self.uploads.buffer(mesh.vertices.into_iter(), BufferUsage::vertex_buffer())?;
+        let (index_buffer, index_future) = self.uploads.buffer(mesh.indices.into_iter(), BufferUsage::index_buffer())?;
 
         self.vertex_buffer = Some(vertex_buffer);
+        self.index_buffer = Some(index_buffer);
 
-        let vs = shader_utils::vs_plane_hmap::Shader::load(self.device.clone()).expect("failed to create shader module");
-        let fs = shader_utils::fs_plane_hmap::Shader::load(self.device.clone()).expect("failed to create shader module");
+        self.shaders.rebuild()?;
+        Ok(Box::new(vertex_future.join(index_future)) as Box<GpuFuture + Send + Sync>)
+    }
+
+    fn build_pipeline(device: &Arc<Device>, subpass: &Subpass<RPass>, vertex_shader: &ShaderModule, fragment_shader: &ShaderModule) -> Result<Arc<GraphicsPipeline<UVBuffer, BPipeline, RPass>>, GraphicsPipelineCreationError>
+    {
+        let vs = unsafe { shader_reload::vertex_entry_point(vertex_shader, shader_utils::vs_plane_hmap::MainInput, shader_utils::vs_plane_hmap::MainOutput, shader_utils::vs_plane_hmap::Layout(shader_reload::vertex_stage())) };
+        let fs = unsafe { shader_reload::fragment_entry_point(fragment_shader, shader_utils::fs_plane_hmap::MainInput, shader_utils::fs_plane_hmap::MainOutput, shader_utils::fs_plane_hmap::Layout(shader_reload::fragment_stage())) };
 
-        let subpass = Subpass::from(self.render_pass.clone(), 0).expect("render pass failed");
-        let pipeline: Arc<GraphicsPipeline<UVBuffer, BPipeline, RPass>> = Arc::new(GraphicsPipeline::start()
+        let pipeline = GraphicsPipeline::start()
             .vertex_input(SingleBufferDefinition::<Vertex3DUV>::new())
-            .vertex_shader(vs.main_entry_point(), ())
+            .vertex_shader(vs, ())
             .triangle_list()
             .viewports_dynamic_scissors_irrelevant(1)
-            .fragment_shader(fs.main_entry_point(), ())
+            .fragment_shader(fs, ())
             .depth_stencil_simple_depth()
-            .render_pass(subpass)
-            .build(self.device.clone())
-            .expect("render pass failed")
+            .render_pass(subpass.clone())
+            .build(device.clone())?;
+        Ok(Arc::new(pipeline))
+    }
+
+    // The pipeline has to exist already, so call it after `create_plane_square`.
+    pub fn set_height_map(&mut self, height_map: Arc<ImmutableImage<R16Unorm>>, sampler: Arc<Sampler>) -> Result<(), VulrustError>
+    {
+        let height_map_descriptor = Arc::new(
+            PersistentDescriptorSet::start(self.shaders.get_pipeline()?, 1)
+
+            .add_sampled_image(height_map, sampler)?
+
+            .build()?
         );
 
-        self.pipeline = Some(pipeline);
+        self.height_map_descriptor = Some(height_map_descriptor);
+        Ok(())
     }
 
-    pub fn get_pipeline(&self) -> Arc<GraphicsPipeline<UVBuffer, BPipeline, RPass>>
+    pub fn get_vertex_buffer(&self) -> Result<Arc<ImmutableBuffer<[Vertex3DUV]>>, VulrustError>
     {
-        match self.pipeline {
-            Some(ref pipeline) => { pipeline.clone() }
-            None => { panic!("Empty pipeline!") }
+        match self.vertex_buffer {
+            Some(ref vertex_buffer) => Ok(vertex_buffer.clone()),
+            None => Err(VulrustError::Missing("vertex buffer"))
         }
     }
 
-    pub fn get_vertex_buffer(&self) -> Arc<CpuAccessibleBuffer<[Vertex3DUV]>>
+    pub fn get_index_buffer(&self) -> Result<Arc<ImmutableBuffer<[u32]>>, VulrustError>
+    {
+        match self.index_buffer {
+            Some(ref index_buffer) => Ok(index_buffer.clone()),
+            None => Err(VulrustError::Missing("index buffer"))
+        }
+    }
+}
+
+impl Drawable for GfxObjectHMap {
+    fn draw(&self, command_buffer_builder: AutoCommandBufferBuilder, dynamic_state: &DynamicState, uniforms: &FrameUniforms) -> Result<AutoCommandBufferBuilder, VulrustError> {
+        let world_uniforms_descriptor = self.shaders.frame_sets.get_or_build(uniforms.frame, || {
+            Ok(Arc::new(
+                PersistentDescriptorSet::start(self.shaders.get_pipeline()?, 0)
+
+                .add_buffer(uniforms.world.clone())?
+
+                .build()?
+            ) as DSet)
+        })?;
+
+        let height_map_descriptor = match self.height_map_descriptor {
+            Some(ref height_map_descriptor) => { height_map_descriptor.clone() }
+            None => return Err(VulrustError::Missing("height map"))
+        };
+
+        let terrain_constants = shader_utils::vs_plane_hmap::ty::TerrainConstants {
+            offset: self.offset,
+            height_scale: self.height_scale
+        };
+
+        Ok(command_buffer_builder.draw_indexed(
+            self.shaders.get_pipeline()?,
+            dynamic_state,
+            self.get_vertex_buffer()?,
+            self.get_index_buffer()?,
+            (world_uniforms_descriptor, height_map_descriptor),
+            terrain_constants
+        )?)
+    }
+
+    fn reload_shaders(&mut self, library: &ShaderLibrary) -> Result<(), ShaderError> {
+        self.shaders.reload(library)
+    }
+}
+
+
+// Textured mesh with its own node transform, what glTF primitives are turned into.
+pub struct GfxObjectMesh {
+    pub device: Arc<Device>,
+    pub uploads: Uploads,
+    pub render_pass: RPass,
+    pub vertex_buffer: Option<Arc<ImmutableBuffer<[Vertex3DNormal3DUV]>>>,
+    pub index_buffer: Option<Arc<ImmutableBuffer<[u32]>>>,
+    pub shaders: ShaderPipeline<MeshBuffer, DSet>,
+    pub transform: Matrix4<f32>,
+    pub base_color: [f32; 4],
+    pub base_color_descriptor: Option<DSet>
+}
+
+impl GfxObjectMesh {
+    pub fn new(device: Arc<Device>, uploads: Uploads, render_pass: RPass) -> Result<GfxObjectMesh, VulrustError> {
+        let shaders = shader_pipeline!("mesh", device, render_pass, vs_mesh, fs_mesh, Vertex3DNormal3DUV::member, GfxObjectMesh::build_pipeline)?;
+        Ok(GfxObjectMesh {
+            device: device.clone(),
+            uploads: uploads,
+            render_pass: render_pass,
+            vertex_buffer: None,
+            index_buffer: None,
+            shaders: shaders,
+            transform: Matrix4::identity(),
+            base_color: [1.0, 1.0, 1.0, 1.0],
+            base_color_descriptor: None
+        })
+    }
+
+    pub fn create_from_mesh(&mut self, mesh: IndexedMesh<Vertex3DNormal3DUV>) -> Result<Box<GpuFuture + Send + Sync>, VulrustError>
+    {
+        let (vertex_buffer, vertex_future) = self.uploads.buffer(mesh.vertices.into_iter(), BufferUsage::vertex_buffer())?;
+        let (index_buffer, index_future) = self.uploads.buffer(mesh.indices.into_iter(), BufferUsage::index_buffer())?;
+
+        self.vertex_buffer = Some(vertex_buffer);
+        self.index_buffer = Some(index_buffer);
+
+        self.shaders.rebuild()?;
+        Ok(Box::new(vertex_future.join(index_future)) as Box<GpuFuture + Send + Sync>)
+    }
+
+    fn build_pipeline(device: &Arc<Device>, subpass: &Subpass<RPass>, vertex_shader: &ShaderModule, fragment_shader: &ShaderModule) -> Result<Arc<GraphicsPipeline<MeshBuffer, BPipeline, RPass>>, GraphicsPipelineCreationError>
+    {
+        let vs = unsafe { shader_reload::vertex_entry_point(vertex_shader, shader_utils::vs_mesh::MainInput, shader_utils::vs_mesh::MainOutput, shader_utils::vs_mesh::Layout(shader_reload::vertex_stage())) };
+        let fs = unsafe { shader_reload::fragment_entry_point(fragment_shader, shader_utils::fs_mesh::MainInput, shader_utils::fs_mesh::MainOutput, shader_utils::fs_mesh::Layout(shader_reload::fragment_stage())) };
+
+        let pipeline = GraphicsPipeline::start()
+            .vertex_input(SingleBufferDefinition::<Vertex3DNormal3DUV>::new())
+            .vertex_shader(vs, ())
+            .triangle_list()
+            .viewports_dynamic_scissors_irrelevant(1)
+            .fragment_shader(fs, ())
+            .depth_stencil_simple_depth()
+            .render_pass(subpass.clone())
+            .build(device.clone())?;
+        Ok(Arc::new(pipeline))
+    }
+
+    // The pipeline has to exist already, so call it after `create_from_mesh`.
+    pub fn set_base_color_texture(&mut self, texture: Arc<ImmutableImage<R8G8B8A8Srgb>>, sampler: Arc<Sampler>) -> Result<(), VulrustError>
+    {
+        let base_color_descriptor = Arc::new(
+            PersistentDescriptorSet::start(self.shaders.get_pipeline()?, 1)
+
+            .add_sampled_image(texture, sampler)?
+
+            .build()?
+        );
+
+        self.base_color_descriptor = Some(base_color_descriptor);
+        Ok(())
+    }
+
+    pub fn get_vertex_buffer(&self) -> Result<Arc<ImmutableBuffer<[Vertex3DNormal3DUV]>>, VulrustError>
     {
         match self.vertex_buffer {
-            Some(ref vertex_buffer) => { vertex_buffer.clone() }
-            None => { panic!("Empty vertex buffer!") }
+            Some(ref vertex_buffer) => Ok(vertex_buffer.clone()),
+            None => Err(VulrustError::Missing("vertex buffer"))
         }
     }
+
+    pub fn get_index_buffer(&self) -> Result<Arc<ImmutableBuffer<[u32]>>, VulrustError>
+    {
+        match self.index_buffer {
+            Some(ref index_buffer) => Ok(index_buffer.clone()),
+            None => Err(VulrustError::Missing("index buffer"))
+        }
+    }
+}
+
+impl Drawable for GfxObjectMesh {
+    fn draw(&self, command_buffer_builder: AutoCommandBufferBuilder, dynamic_state: &DynamicState, uniforms: &FrameUniforms) -> Result<AutoCommandBufferBuilder, VulrustError> {
+        let world_uniforms_descriptor = self.shaders.frame_sets.get_or_build(uniforms.frame, || {
+            Ok(Arc::new(
+                PersistentDescriptorSet::start(self.shaders.get_pipeline()?, 0)
+
+                .add_buffer(uniforms.world.clone())?
+
+                .build()?
+            ) as DSet)
+        })?;
+
+        let base_color_descriptor = match self.base_color_descriptor {
+            Some(ref base_color_descriptor) => { base_color_descriptor.clone() }
+            None => return Err(VulrustError::Missing("base color texture"))
+        };
+
+        let mesh_constants = shader_utils::vs_mesh::ty::MeshConstants {
+            node: self.transform.into(),
+            base_color: self.base_color
+        };
+
+        Ok(command_buffer_builder.draw_indexed(
+            self.shaders.get_pipeline()?,
+            dynamic_state,
+            self.get_vertex_buffer()?,
+            self.get_index_buffer()?,
+            (world_uniforms_descriptor, base_color_descriptor),
+            mesh_constants
+        )?)
+    }
+
+    fn reload_shaders(&mut self, library: &ShaderLibrary) -> Result<(), ShaderError> {
+        self.shaders.reload(library)
+    }
 }
@@ -0,0 +1,218 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use cgmath::Matrix4;
+use cgmath::SquareMatrix;
+use gltf;
+use gltf::image::Format;
+use gltf::mesh::Mode;
+
+use geometry::IndexedMesh;
+use math_utils::face_normal;
+use vertex_types::Vertex3DNormal3DUV;
+
+
+#[derive(Debug)]
+pub enum GltfError {
+    Import(PathBuf, gltf::Error),
+    MissingPositions { path: PathBuf, mesh: String },
+    // A vertex attribute with another count than the positions.
+    AttributeCount { path: PathBuf, mesh: String, attribute: &'static str, count: usize, positions: usize },
+    IndexOutOfRange { path: PathBuf, mesh: String, index: u32, positions: usize }
+}
+
+impl fmt::Display for GltfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GltfError::Import(ref path, ref err) => write!(f, "{}: {}", path.display(), err),
+            GltfError::MissingPositions { ref path, ref mesh } => write!(f, "{}: mesh {:?} has a primitive without positions", path.display(), mesh),
+            GltfError::AttributeCount { ref path, ref mesh, attribute, count, positions } =>
+                write!(f, "{}: mesh {:?} has {} {} for {} positions", path.display(), mesh, count, attribute, positions),
+            GltfError::IndexOutOfRange { ref path, ref mesh, index, positions } =>
+                write!(f, "{}: mesh {:?} has index {} for {} positions", path.display(), mesh, index, positions)
+        }
+    }
+}
+
+// One glTF primitive placed in the scene, a triangle list over shared vertices.
+#[derive(Debug, Clone)]
+pub struct GltfPrimitive {
+    pub mesh: String,
+    // Product of the node transforms from the scene root down to the node holding the mesh.
+    pub transform: Matrix4<f32>,
+    pub geometry: IndexedMesh<Vertex3DNormal3DUV>,
+    pub base_color: [f32; 4],
+    // Index into `GltfScene::textures`.
+    pub base_color_texture: Option<usize>
+}
+
+// Image converted to tightly packed RGBA8, whatever the source format was.
+#[derive(Debug, Clone)]
+pub struct GltfTexture {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GltfScene {
+    pub primitives: Vec<GltfPrimitive>,
+    pub textures: Vec<GltfTexture>
+}
+
+// Loads the default scene of a `.gltf` or `.glb` file, or the first one if none is marked as default.
+pub fn load_gltf<P: AsRef<Path>>(path: P) -> Result<GltfScene, GltfError> {
+    let path = path.as_ref();
+    let (document, buffers, images) = gltf::import(path).map_err(|err| GltfError::Import(path.to_path_buf(), err))?;
+
+    let mut scene = GltfScene {
+        primitives: Vec::new(),
+        textures: images.iter().map(to_texture).collect()
+    };
+
+    let root = document.default_scene().or_else(|| document.scenes().next());
+    if let Some(root) = root {
+        for node in root.nodes() {
+            visit_node(path, &node, Matrix4::identity(), &buffers, &mut scene.primitives)?;
+        }
+    }
+
+    Ok(scene)
+}
+
+fn visit_node(
+    path: &Path,
+    node: &gltf::Node,
+    parent: Matrix4<f32>,
+    buffers: &[gltf::buffer::Data],
+    primitives: &mut Vec<GltfPrimitive>
+) -> Result<(), GltfError> {
+    let transform = parent * Matrix4::from(node.transform().matrix());
+
+    if let Some(mesh) = node.mesh() {
+        let name = mesh.name().unwrap_or("default").to_string();
+        for primitive in mesh.primitives() {
+            // Points and lines aren't drawn.
+            let mode = primitive.mode();
+            if mode != Mode::Triangles && mode != Mode::TriangleStrip && mode != Mode::TriangleFan {
+                continue;
+            }
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+            let pbr = primitive.material().pbr_metallic_roughness();
+            let base_color_texture = pbr.base_color_texture();
+
+            let positions: Vec<[f32; 3]> = match reader.read_positions() {
+                Some(positions) => positions.collect(),
+                None => return Err(GltfError::MissingPositions { path: path.to_path_buf(), mesh: name })
+            };
+            let normals: Option<Vec<[f32; 3]>> = reader.read_normals().map(|normals| normals.collect());
+            // The UV set the base color texture is sampled with.
+            let uv_set = base_color_texture.as_ref().map(|info| info.tex_coord()).unwrap_or(0);
+            let uvs: Option<Vec<[f32; 2]>> = reader.read_tex_coords(uv_set).map(|uvs| uvs.into_f32().collect());
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0 .. positions.len() as u32).collect()
+            };
+            let indices = triangle_list(mode, indices);
+
+            let attribute_count = |attribute, count| {
+                if count == positions.len() {
+                    Ok(())
+                } else {
+                    Err(GltfError::AttributeCount { path: path.to_path_buf(), mesh: name.clone(), attribute: attribute, count: count, positions: positions.len() })
+                }
+            };
+            if let Some(ref normals) = normals {
+                attribute_count("normals", normals.len())?;
+            }
+            if let Some(ref uvs) = uvs {
+                attribute_count("UVs", uvs.len())?;
+            }
+            if let Some(&index) = indices.iter().find(|&&index| index as usize >= positions.len()) {
+                return Err(GltfError::IndexOutOfRange { path: path.to_path_buf(), mesh: name, index: index, positions: positions.len() });
+            }
+
+            // Without normals in the file, every vertex gets the average of the faces around it.
+            let normals = normals.unwrap_or_else(|| smooth_normals(&positions, &indices));
+            let vertices = positions.iter().enumerate().map(|(i, &position)| Vertex3DNormal3DUV {
+                position: position,
+                normal: normals[i],
+                uv: uvs.as_ref().map(|t| t[i]).unwrap_or([0.0, 0.0])
+            }).collect();
+
+            primitives.push(GltfPrimitive {
+                mesh: name.clone(),
+                transform: transform,
+                geometry: IndexedMesh { vertices: vertices, indices: indices },
+                base_color: pbr.base_color_factor(),
+                base_color_texture: base_color_texture.map(|info| info.texture().source().index())
+            });
+        }
+    }
+
+    for child in node.children() {
+        visit_node(path, &child, transform, buffers, primitives)?;
+    }
+
+    Ok(())
+}
+
+// Strips and fans as a list with the same winding.
+fn triangle_list(mode: Mode, indices: Vec<u32>) -> Vec<u32> {
+    let triangles = indices.len().saturating_sub(2);
+    match mode {
+        Mode::TriangleStrip => (0 .. triangles).flat_map(|i| {
+            // Every other triangle of a strip is flipped.
+            if i % 2 == 0 {
+                vec![indices[i], indices[i + 1], indices[i + 2]]
+            } else {
+                vec![indices[i + 1], indices[i], indices[i + 2]]
+            }
+        }).collect(),
+        Mode::TriangleFan => (0 .. triangles).flat_map(|i| vec![indices[0], indices[i + 1], indices[i + 2]]).collect(),
+        _ => indices
+    }
+}
+
+fn smooth_normals(positions: &[[f32; 3]], indices: &[u32]) -> Vec<[f32; 3]> {
+    let mut sums = vec![[0.0f32; 3]; positions.len()];
+    for triangle in indices.chunks(3).filter(|triangle| triangle.len() == 3) {
+        let n = face_normal(positions[triangle[0] as usize], positions[triangle[1] as usize], positions[triangle[2] as usize]);
+        for &i in triangle.iter() {
+            let sum = &mut sums[i as usize];
+            sum[0] += n[0];
+            sum[1] += n[1];
+            sum[2] += n[2];
+        }
+    }
+    sums.iter().map(|n| {
+        let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+        if length == 0.0 { [0.0, 1.0, 0.0] } else { [n[0] / length, n[1] / length, n[2] / length] }
+    }).collect()
+}
+
+fn to_texture(image: &gltf::image::Data) -> GltfTexture {
+    let pixels: Vec<u8> = match image.format {
+        Format::R8 => image.pixels.iter().flat_map(|&r| vec![r, r, r, 255]).collect(),
+        Format::R8G8 => image.pixels.chunks(2).flat_map(|p| vec![p[0], p[1], 0, 255]).collect(),
+        Format::R8G8B8 => image.pixels.chunks(3).flat_map(|p| vec![p[0], p[1], p[2], 255]).collect(),
+        Format::R8G8B8A8 => image.pixels.clone(),
+        Format::B8G8R8 => image.pixels.chunks(3).flat_map(|p| vec![p[2], p[1], p[0], 255]).collect(),
+        Format::B8G8R8A8 => image.pixels.chunks(4).flat_map(|p| vec![p[2], p[1], p[0], p[3]]).collect(),
+        // 16-bit channels are stored in native byte order, keep the high byte.
+        Format::R16 => high_bytes(&image.pixels).chunks(1).flat_map(|p| vec![p[0], p[0], p[0], 255]).collect(),
+        Format::R16G16 => high_bytes(&image.pixels).chunks(2).flat_map(|p| vec![p[0], p[1], 0, 255]).collect(),
+        Format::R16G16B16 => high_bytes(&image.pixels).chunks(3).flat_map(|p| vec![p[0], p[1], p[2], 255]).collect(),
+        Format::R16G16B16A16 => high_bytes(&image.pixels)
+    };
+
+    GltfTexture {
+        width: image.width,
+        height: image.height,
+        pixels: pixels
+    }
+}
+
+fn high_bytes(pixels: &[u8]) -> Vec<u8> {
+    pixels.chunks(2).map(|c| (u16::from_ne_bytes([c[0], c[1]]) >> 8) as u8).collect()
+}
@@ -0,0 +1,165 @@
+use std::sync::Arc;
+use vulkano::buffer::BufferUsage;
+use vulkano::buffer::cpu_access::CpuAccessibleBuffer;
+use vulkano::command_buffer::AutoCommandBufferBuilder;
+use vulkano::command_buffer::CommandBuffer;
+use vulkano::command_buffer::DynamicState;
+use vulkano::format::Format;
+use vulkano::framebuffer::Framebuffer;
+use vulkano::image::ImageUsage;
+use vulkano::image::attachment::AttachmentImage;
+use vulkano::instance::InstanceExtensions;
+use vulkano::pipeline::viewport::Viewport;
+use vulkano::sync::GpuFuture;
+use image;
+use rand::SeedableRng;
+use rand::prng::XorShiftRng;
+
+use device_selection;
+use device_selection::{DevicePreference, DeviceRequirements};
+use drawable;
+use drawable::FrameUniforms;
+use error::VulrustError;
+use frames_in_flight;
+use scene::Scene;
+use scene::SceneParts;
+use terrain::TerrainDesc;
+use upload::Uploads;
+use validation;
+use validation::ValidationMode;
+use world::World;
+
+
+// What the frame is rendered into. sRGB like the swapchain format the window prefers, see
+// `present_mode::choose_surface_format`, so both gamma encode the same way.
+pub const COLOR_FORMAT: Format = Format::R8G8B8A8Srgb;
+
+pub struct HeadlessOptions {
+    pub path: String,
+    pub dimensions: [u32; 2],
+    pub parts: SceneParts,
+    pub terrain: TerrainDesc,
+    pub seed: u64,
+    pub device: DevicePreference,
+    pub validation: ValidationMode
+}
+
+// Renders a single frame of the scene without a window and saves it as an RGBA PNG.
+// Needs no surface or swapchain support, so it runs on software drivers like lavapipe.
+// The frame is fully determined by the options: the spin delta is zero and the RNG is seeded.
+pub fn render_to_png(options: &HeadlessOptions) -> Result<(), VulrustError> {
+    let path = options.path.as_str();
+    let dimensions = options.dimensions;
+
+    /* ##########
+    INSTANCE
+    ########## */
+    println!("Instance.");
+    let (instance, validation) = validation::create_instance(InstanceExtensions::none(), options.validation)?;
+
+    /* ##########
+    PHYSICAL DEVICE
+    ########## */
+    println!("Physical device.");
+    let requirements = DeviceRequirements::headless();
+    let (physical_device, queue_families) = device_selection::select_device(&instance, &requirements, None, &options.device)?;
+
+    /* ##########
+    DEVICE
+    ########## */
+    println!("Device.");
+    let (device, queues) = device_selection::create_device(physical_device, &requirements, queue_families)?;
+
+    /* ##########
+    RENDERPASS
+    ########## */
+    println!("Renderpass.");
+    let render_pass = Arc::new(single_pass_renderpass!(
+        device.clone(),
+        attachments: {
+            color: {
+                load: Clear,
+                store: Store,
+                format: COLOR_FORMAT,
+                samples: 1,
+            },
+            depth: {
+                load: Clear,
+                store: DontCare,
+                format: Format::D16Unorm,
+                samples: 1,
+            }
+        },
+        pass: {
+            color: [color],
+            depth_stencil: {depth}
+        }
+    )?);
+
+    /* ##########
+    FRAMEBUFFER
+    ########## */
+    println!("Framebuffer.");
+    let color_usage = ImageUsage {
+        color_attachment: true,
+        transfer_source: true,
+        .. ImageUsage::none()
+    };
+    let color_buffer = AttachmentImage::with_usage(device.clone(), dimensions, COLOR_FORMAT, color_usage)?;
+    let depth_buffer = AttachmentImage::transient(device.clone(), dimensions, Format::D16Unorm)?;
+    let framebuffer = Arc::new(
+        Framebuffer::start(render_pass.clone())
+        .add(color_buffer.clone())?
+        .add(depth_buffer.clone())?
+        .build()?
+    );
+
+    let mut rng = XorShiftRng::seed_from_u64(options.seed);
+    let (scene, scene_future) = Scene::new(device.clone(), &Uploads::new(&queues), render_pass.clone(), options.parts, &options.terrain, &mut rng)?;
+
+    let world_uniforms = frames_in_flight::world_uniforms_buffer(device.clone())?;
+    let mut world = World::new();
+    world.set_aspect_ratio(dimensions[0] as f32 / dimensions[1] as f32);
+    let uniforms = FrameUniforms::new(&world_uniforms, 0, &world)?;
+
+    let dynamic_state = DynamicState {
+        line_width: None,
+        viewports: Some(vec![Viewport {
+            origin: [0.0, 0.0],
+            dimensions: [dimensions[0] as f32, dimensions[1] as f32],
+            depth_range: 0.0 .. 1.0,
+        }]),
+        scissors: None,
+    };
+
+    let output_buffer = CpuAccessibleBuffer::from_iter(
+        device.clone(),
+        BufferUsage::all(),
+        (0 .. dimensions[0] * dimensions[1] * 4).map(|_| 0u8)
+    )?;
+
+    /* ##########
+    RENDER
+    ########## */
+    println!("Render.");
+    let mut command_buffer_builder = AutoCommandBufferBuilder::primary_one_time_submit(device.clone(), queues.graphics.family())?
+        .begin_render_pass(framebuffer.clone(), false, vec![[0.0, 0.0, 0.0, 1.0].into(), 1f32.into()])?;
+
+    command_buffer_builder = drawable::draw_all(command_buffer_builder, &dynamic_state, &uniforms, &scene.drawables)?;
+
+    let command_buffer = command_buffer_builder
+        .end_render_pass()?
+
+        .copy_image_to_buffer(color_buffer.clone(), output_buffer.clone())?
+
+        .build()?;
+
+    command_buffer.execute_after(scene_future, queues.graphics.clone())?
+        .then_signal_fence_and_flush()?
+        .wait(None)?;
+
+    let pixels = output_buffer.read()?;
+    image::save_buffer(path, &pixels, dimensions[0], dimensions[1], image::RGBA(8)).map_err(|err| VulrustError::Io(path.into(), err))?;
+    println!("Saved frame to {}", path);
+    validation.check()
+}
@@ -0,0 +1,295 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use winit::{ElementState, MouseButton, VirtualKeyCode, WindowEvent};
+
+
+// Axes count as held once they are deflected this far towards the bound side.
+const AXIS_THRESHOLD: f64 = 0.5;
+
+// Everything the user can ask for, independent of the key or button that asks for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    MoveForward,
+    MoveBackward,
+    StrafeLeft,
+    StrafeRight,
+    MoveUp,
+    MoveDown,
+    TurnLeft,
+    TurnRight,
+    LookUp,
+    LookDown,
+    ToggleWalking,
+    ToggleCursor,
+    // Vsync and the other present modes, see `PresentPreference::next`.
+    CyclePresentMode
+}
+
+const ACTIONS: [Action; 13] = [
+    Action::MoveForward,
+    Action::MoveBackward,
+    Action::StrafeLeft,
+    Action::StrafeRight,
+    Action::MoveUp,
+    Action::MoveDown,
+    Action::TurnLeft,
+    Action::TurnRight,
+    Action::LookUp,
+    Action::LookDown,
+    Action::ToggleWalking,
+    Action::ToggleCursor,
+    Action::CyclePresentMode
+];
+
+impl Action {
+    // Config files use the variant names, `MoveForward` and so on.
+    pub fn from_name(name: &str) -> Option<Action> {
+        ACTIONS.iter().cloned().find(|action| format!("{:?}", action) == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Binding {
+    Key(VirtualKeyCode),
+    Mouse(MouseButton),
+    // An analog axis pushed past `AXIS_THRESHOLD` towards the positive or negative side.
+    Axis { axis: u32, positive: bool }
+}
+
+impl Binding {
+    // Key names are the `VirtualKeyCode` variants (`W`, `Up`, `LShift`...), mouse buttons are
+    // `MouseLeft`, `MouseRight`, `MouseMiddle` or `Mouse<n>`, axes are `Axis<n>+` or `Axis<n>-`.
+    pub fn from_name(name: &str) -> Option<Binding> {
+        if let Some(key) = key_from_name(name) {
+            return Some(Binding::Key(key));
+        }
+        if let Some(axis) = name.strip_prefix("Axis") {
+            let (axis, positive) = match (axis.strip_suffix('+'), axis.strip_suffix('-')) {
+                (Some(axis), _) => (axis, true),
+                (_, Some(axis)) => (axis, false),
+                _ => return None
+            };
+            return axis.parse().ok().map(|axis| Binding::Axis { axis: axis, positive: positive });
+        }
+        match name {
+            "MouseLeft" => Some(Binding::Mouse(MouseButton::Left)),
+            "MouseRight" => Some(Binding::Mouse(MouseButton::Right)),
+            "MouseMiddle" => Some(Binding::Mouse(MouseButton::Middle)),
+            _ => name.strip_prefix("Mouse").and_then(|button| button.parse().ok()).map(|button| Binding::Mouse(MouseButton::Other(button)))
+        }
+    }
+}
+
+macro_rules! key_names {
+    ($($key:ident),*) => {
+        fn key_from_name(name: &str) -> Option<VirtualKeyCode> {
+            match name {
+                $(stringify!($key) => Some(VirtualKeyCode::$key),)*
+                _ => None
+            }
+        }
+    }
+}
+
+key_names!(
+    Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0,
+    A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    Escape, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    Insert, Home, Delete, End, PageDown, PageUp, Left, Up, Right, Down,
+    Back, Return, Space, Tab,
+    Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+    Add, Subtract, Multiply, Divide, Decimal, NumpadEnter,
+    LAlt, LControl, LShift, LWin, RAlt, RControl, RShift, RWin,
+    Apostrophe, Backslash, Comma, Equals, Grave, LBracket, RBracket, Minus, Period, Semicolon, Slash
+);
+
+#[derive(Debug)]
+pub enum InputMapError {
+    Io(PathBuf, io::Error),
+    // Lines are counted from 1.
+    Syntax(usize, String),
+    UnknownAction(usize, String),
+    UnknownBinding(usize, String)
+}
+
+impl fmt::Display for InputMapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            InputMapError::Io(ref path, ref err) => write!(f, "{}: {}", path.display(), err),
+            InputMapError::Syntax(line, ref text) => write!(f, "line {}: expected `Action = binding, ...`, got `{}`", line, text),
+            InputMapError::UnknownAction(line, ref name) => write!(f, "line {}: unknown action `{}`", line, name),
+            InputMapError::UnknownBinding(line, ref name) => write!(f, "line {}: unknown key, button or axis `{}`", line, name)
+        }
+    }
+}
+
+// Which bindings trigger which action. Several bindings may share an action and one binding
+// may trigger several actions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputMap {
+    pub bindings: Vec<(Binding, Action)>
+}
+
+impl Default for InputMap {
+    fn default() -> InputMap {
+        let keys = [
+            (VirtualKeyCode::W, Action::MoveForward),
+            (VirtualKeyCode::Up, Action::MoveForward),
+            (VirtualKeyCode::S, Action::MoveBackward),
+            (VirtualKeyCode::Down, Action::MoveBackward),
+            (VirtualKeyCode::A, Action::StrafeLeft),
+            (VirtualKeyCode::D, Action::StrafeRight),
+            (VirtualKeyCode::Space, Action::MoveUp),
+            (VirtualKeyCode::LShift, Action::MoveDown),
+            (VirtualKeyCode::Left, Action::TurnLeft),
+            (VirtualKeyCode::Right, Action::TurnRight),
+            (VirtualKeyCode::PageUp, Action::LookUp),
+            (VirtualKeyCode::PageDown, Action::LookDown),
+            (VirtualKeyCode::G, Action::ToggleWalking),
+            (VirtualKeyCode::Tab, Action::ToggleCursor),
+            (VirtualKeyCode::V, Action::CyclePresentMode)
+        ];
+        InputMap {
+            bindings: keys.iter().map(|&(key, action)| (Binding::Key(key), action)).collect()
+        }
+    }
+}
+
+impl InputMap {
+    // Reads `Action = binding, binding` lines on top of the default map, every action that
+    // shows up loses its default bindings. `#` starts a comment, an empty list unbinds.
+    pub fn parse(config: &str) -> Result<InputMap, InputMapError> {
+        let mut map = InputMap::default();
+        let mut rebound: Vec<Action> = Vec::new();
+        for (number, line) in config.lines().enumerate() {
+            let number = number + 1;
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut sides = line.splitn(2, '=');
+            let (name, bindings) = match (sides.next(), sides.next()) {
+                (Some(name), Some(bindings)) => (name.trim(), bindings),
+                _ => return Err(InputMapError::Syntax(number, line.to_string()))
+            };
+            let action = Action::from_name(name).ok_or_else(|| InputMapError::UnknownAction(number, name.to_string()))?;
+            if !rebound.contains(&action) {
+                map.bindings.retain(|&(_, bound)| bound != action);
+                rebound.push(action);
+            }
+            for binding in bindings.split(',').map(|name| name.trim()).filter(|name| !name.is_empty()) {
+                let binding = Binding::from_name(binding).ok_or_else(|| InputMapError::UnknownBinding(number, binding.to_string()))?;
+                map.bindings.push((binding, action));
+            }
+        }
+        Ok(map)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<InputMap, InputMapError> {
+        let path = path.as_ref();
+        let config = fs::read_to_string(path).map_err(|err| InputMapError::Io(path.to_path_buf(), err))?;
+        InputMap::parse(&config)
+    }
+
+    pub fn actions(&self, binding: Binding) -> impl Iterator<Item = Action> + '_ {
+        self.bindings.iter().filter(move |&&(bound, _)| bound == binding).map(|&(_, action)| action)
+    }
+}
+
+// Per-frame view of the input: feed it events, query the actions, then call `end_frame`.
+// Pressed and released only report the frame the change happened in, held lasts as long as
+// any binding of the action stays down.
+pub struct InputState {
+    pub map: InputMap,
+    down: Vec<Binding>,
+    went_down: Vec<Binding>,
+    went_up: Vec<Binding>
+}
+
+impl InputState {
+    pub fn new(map: InputMap) -> InputState {
+        InputState {
+            map: map,
+            down: Vec::new(),
+            went_down: Vec::new(),
+            went_up: Vec::new()
+        }
+    }
+
+    // Repeated presses from a key held down are ignored.
+    pub fn press(&mut self, binding: Binding) {
+        if !self.down.contains(&binding) {
+            self.down.push(binding);
+            self.went_down.push(binding);
+        }
+    }
+
+    pub fn release(&mut self, binding: Binding) {
+        if self.down.contains(&binding) {
+            self.down.retain(|&down| down != binding);
+            self.went_up.push(binding);
+        }
+    }
+
+    // Lets go of everything, e.g. when the window loses focus and the releases would be missed.
+    pub fn release_all(&mut self) {
+        let down = self.down.clone();
+        for binding in down {
+            self.release(binding);
+        }
+    }
+
+    pub fn set_axis(&mut self, axis: u32, value: f64) {
+        for &positive in [true, false].iter() {
+            let binding = Binding::Axis { axis: axis, positive: positive };
+            let deflection = if positive { value } else { -value };
+            if deflection >= AXIS_THRESHOLD { self.press(binding) } else { self.release(binding) }
+        }
+    }
+
+    // Picks out the keyboard, mouse button and axis events, everything else is left alone.
+    pub fn handle_window_event(&mut self, event: &WindowEvent) {
+        match *event {
+            WindowEvent::KeyboardInput { input, .. } => {
+                if let Some(key) = input.virtual_keycode {
+                    self.set(Binding::Key(key), input.state);
+                }
+            },
+            WindowEvent::MouseInput { state, button, .. } => self.set(Binding::Mouse(button), state),
+            WindowEvent::AxisMotion { axis, value, .. } => self.set_axis(axis, value),
+            WindowEvent::Focused(false) => self.release_all(),
+            _ => ()
+        }
+    }
+
+    pub fn held(&self, action: Action) -> bool {
+        self.down.iter().any(|&binding| self.triggers(binding, action))
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.went_down.iter().any(|&binding| self.triggers(binding, action))
+    }
+
+    pub fn released(&self, action: Action) -> bool {
+        self.went_up.iter().any(|&binding| self.triggers(binding, action))
+    }
+
+    pub fn end_frame(&mut self) {
+        self.went_down.clear();
+        self.went_up.clear();
+    }
+
+    fn set(&mut self, binding: Binding, state: ElementState) {
+        match state {
+            ElementState::Pressed => self.press(binding),
+            ElementState::Released => self.release(binding)
+        }
+    }
+
+    fn triggers(&self, binding: Binding, action: Action) -> bool {
+        self.map.actions(binding).any(|bound| bound == action)
+    }
+}
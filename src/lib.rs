@@ -0,0 +1,46 @@
+#[macro_use]
+extern crate vulkano;
+extern crate vulkano_win;
+extern crate vulkano_shaders;
+extern crate vk_sys;
+extern crate winit;
+extern crate cgmath;
+extern crate rand;
+extern crate image;
+extern crate png;
+extern crate gltf;
+extern crate shaderc;
+
+pub mod error;
+pub mod global;
+pub mod math_utils;
+pub mod shader_utils;
+pub mod shader_reload;
+pub mod shader_interface;
+pub mod vertex_types;
+
+pub mod rectangle_instance_builder;
+pub mod rectangle_instance;
+
+pub mod drawable;
+pub mod geometry;
+pub mod gfx_object;
+pub mod obj_loader;
+pub mod gltf_loader;
+pub mod camera;
+pub mod benchmark;
+pub mod frame_timer;
+pub mod game_loop;
+pub mod input;
+pub mod mouse;
+pub mod scene;
+pub mod terrain;
+pub mod world;
+
+pub mod validation;
+pub mod device_selection;
+pub mod present_mode;
+pub mod frames_in_flight;
+pub mod upload;
+pub mod renderer;
+pub mod headless;
@@ -1,545 +1,374 @@
-#[macro_use]
-extern crate vulkano;
-extern crate vulkano_win;
-extern crate vulkano_shaders;
+extern crate vulrust;
 extern crate winit;
-extern crate cgmath;
-extern crate time;
 extern crate rand;
-extern crate image;
-
-use std::sync::Arc;
-use vulkano::buffer::BufferUsage;
-use vulkano::buffer::cpu_access::CpuAccessibleBuffer;
-use vulkano::buffer::cpu_pool::CpuBufferPool;
-use vulkano::command_buffer::AutoCommandBufferBuilder;
-use vulkano::command_buffer::DynamicState;
-use vulkano::descriptor::descriptor_set::PersistentDescriptorSet;
-use vulkano::device::Device;
-use vulkano::device::DeviceExtensions;
-use vulkano::device::Features;
-use vulkano::image::ImageUsage;
-use vulkano::framebuffer::Framebuffer;
-use vulkano::image::immutable::ImmutableImage;
-use vulkano::instance::Instance;
-use vulkano::instance::InstanceExtensions;
-use vulkano::instance::PhysicalDevice;
-use vulkano::pipeline::viewport::Viewport;
-use vulkano::swapchain::Swapchain;
-use vulkano::sync::now;
-use vulkano::sync::GpuFuture;
-use vulkano::sync::SharingMode;
-use vulkano_win::VkSurfaceBuild;
-use winit::EventsLoop;
-use winit::WindowBuilder;
-use winit::VirtualKeyCode;
-use winit::dpi::LogicalSize;
-
-mod global;
-use global::*;
-
-mod math_utils;
-mod shader_utils;
-mod vertex_types;
-
-mod rectangle_instance_builder;
-use rectangle_instance_builder::RectangleInstanceBuilder;
-
-mod rectangle_instance;
-use rectangle_instance::RectangleInstance;
-
-mod gfx_object;
-use gfx_object::GfxObject;
-use gfx_object::GfxObject3D;
-use gfx_object::GfxObjectHMap;
+extern crate cgmath;
 
-mod world;
-use world::World;
+use std::env;
+use std::str::FromStr;
+use std::time::Instant;
+use winit::EventsLoop;
 
-use cgmath::{Point3, Vector3, Matrix4, Matrix, Rad, perspective, One};
+use vulrust::benchmark::{FrameStats, WARMUP_FRAMES};
+use vulrust::device_selection::DevicePreference;
+use vulrust::error::VulrustError;
+use vulrust::frames_in_flight;
+use vulrust::frames_in_flight::DEFAULT_FRAMES_IN_FLIGHT;
+use vulrust::game_loop;
+use vulrust::game_loop::GameLoop;
+use vulrust::global::*;
+use vulrust::gltf_loader;
+use vulrust::headless;
+use vulrust::headless::HeadlessOptions;
+use vulrust::input::{Action, InputMap, InputState};
+use vulrust::mouse::MouseConfig;
+use vulrust::mouse::WheelAction;
+use vulrust::present_mode::{PresentPreference, PRESENT_MODE_ENV_VAR};
+use vulrust::renderer::Renderer;
+use vulrust::renderer::RendererConfig;
+use vulrust::scene::Scene;
+use vulrust::scene::SceneParts;
+use vulrust::shader_reload::ShaderLibrary;
+use vulrust::terrain::TerrainDesc;
+use vulrust::upload::Uploads;
+use vulrust::validation::ValidationMode;
+use vulrust::world::World;
+
+
+// Touchpads scroll in pixels, count this many as one wheel notch.
+const PIXELS_PER_LINE: f32 = 20.0;
+// Fixed updates per second, unless `--tick-rate` says otherwise.
+const TICK_RATE: f32 = 60.0;
+// How often the `--shader-dir` directory is checked for changed shaders.
+const SHADER_POLL_FRAMES: i64 = 30;
+
+// What both the fixed updates and the frames work on.
+struct Game {
+    world: World,
+    input: InputState
+}
 
+// One fixed step of the simulation.
+fn update(game: &mut Game, dt: f32) {
+    let world = &mut game.world;
+    let input = &game.input;
+    world.begin_tick();
+
+    if input.held(Action::MoveForward) { world.move_forwards(dt); }
+    if input.held(Action::MoveBackward) { world.move_backwards(dt); }
+    if input.held(Action::StrafeLeft) { world.strafe_left(dt); }
+    if input.held(Action::StrafeRight) { world.strafe_right(dt); }
+    if input.held(Action::MoveUp) { world.move_up(dt); }
+    if input.held(Action::MoveDown) { world.move_down(dt); }
+    if input.held(Action::TurnLeft) { world.turn_left(dt); }
+    if input.held(Action::TurnRight) { world.turn_right(dt); }
+    if input.held(Action::LookUp) { world.look_up(dt); }
+    if input.held(Action::LookDown) { world.look_down(dt); }
+
+    world.follow_ground(dt);
+    world.animate(dt);
+}
 
 fn avoid_winit_wayland_hack() {
     println!("Force X11.");
     std::env::set_var("WINIT_UNIX_BACKEND", "x11");
 }
 
-fn load_image_sample() -> image::RgbImage {
-    image::open("./fixtures/97295-mountain2-height-map-merged.png").unwrap().to_rgb()
+// Value that follows `name` on the command line, e.g. `--scene cube`.
+fn arg_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|position| args.get(position + 1))
+        .filter(|value| !value.starts_with("--"))
+        .map(|value| value.as_str())
 }
 
-fn main() {
-    if cfg!(target_os = "linux") {
-        avoid_winit_wayland_hack();
+// `--validation` alone turns validation on, `--validation strict` also fails the run on
+// validation errors. Without the flag `VULRUST_VALIDATION` decides.
+fn validation_mode(args: &[String]) -> Result<ValidationMode, VulrustError> {
+    if !args.iter().any(|arg| arg == "--validation") {
+        return ValidationMode::from_env();
     }
+    match arg_value(args, "--validation") {
+        Some(mode) => ValidationMode::parse(mode).ok_or_else(|| VulrustError::invalid_argument("--validation", mode)),
+        None => Ok(ValidationMode::On)
+    }
+}
 
-    load_image_sample();
-
-    /* ##########
-    INSTANCE
-    ########## */
-    println!("Instance.");
-    let instance_extensions = InstanceExtensions::supported_by_core().unwrap();
-    let instance = Instance::new(None, &instance_extensions, None).unwrap();
-
-    /* ##########
-    PHYSICAL DEVICE
-    ########## */
-    println!("Physical device.");
-    let physical_device = {
-        let mut physical_devices = PhysicalDevice::enumerate(&instance);
-        // physical_devices.next().unwrap();
-        physical_devices.next().unwrap()
-    };
-    println!("{:?}", physical_device.name());
-
-    /* ##########
-    DEVICE
-    ########## */
-    println!("Device.");
-    let (device, mut queue_iter) = {
-        let queue_family = physical_device.queue_families().next().unwrap();
-        let features = Features::none();
-        let ext = DeviceExtensions {
-            khr_swapchain: true,
-            .. DeviceExtensions::none()
-        };
+// `--present-mode`, else `VULRUST_PRESENT_MODE`. A benchmark defaults to immediate, so vsync
+// doesn't hide the CPU frame time.
+fn present_mode(args: &[String], benchmark: bool) -> Result<PresentPreference, VulrustError> {
+    if let Some(mode) = arg_value(args, "--present-mode") {
+        return PresentPreference::parse(mode).ok_or_else(|| VulrustError::invalid_argument("--present-mode", mode));
+    }
+    if benchmark && env::var(PRESENT_MODE_ENV_VAR).is_err() {
+        return Ok(PresentPreference::Immediate);
+    }
+    PresentPreference::from_env()
+}
 
-        match Device::new(physical_device, &features, &ext, Some((queue_family, 1.0))) {
-            Ok(d) => d,
-            Err(err) => panic!("Couldn't build device: {:?}", err)
-        }
-    };
+// Value of `name` parsed as a `T`, `None` if the option isn't given.
+fn parse_arg<T: FromStr>(args: &[String], name: &str) -> Result<Option<T>, VulrustError> {
+    match arg_value(args, name) {
+        Some(value) => value.parse().map(Some).map_err(|_| VulrustError::invalid_argument(name, value)),
+        None => Ok(None)
+    }
+}
 
-    /* ##########
-    QUEUE
-    ########## */
-    println!("Queue.");
-    let present_queue = queue_iter.next().unwrap();
+// Like `parse_arg`, but also rejects values `valid` doesn't accept.
+fn parse_arg_if<T: FromStr, F: Fn(&T) -> bool>(args: &[String], name: &str, valid: F) -> Result<Option<T>, VulrustError> {
+    match parse_arg(args, name)? {
+        Some(ref value) if !valid(value) => Err(VulrustError::invalid_argument(name, arg_value(args, name).unwrap_or(""))),
+        value => Ok(value)
+    }
+}
 
-    /* ##########
-    WINDOW
-    ########## */
-    println!("Window.");
-    let window_builder = WindowBuilder::new().with_dimensions(LogicalSize::new(SCR_WIDTH as f64, SCR_HEIGHT as f64));
-    let mut events_loop = EventsLoop::new();
-    let surface = window_builder.build_vk_surface(&events_loop, instance.clone()).unwrap();
+// Terrain parameters from `--heightmap`, `--terrain-resolution`, `--cell-size` and `--height-scale`.
+fn terrain_desc(args: &[String]) -> Result<TerrainDesc, VulrustError> {
+    let mut terrain = TerrainDesc::default();
+    if let Some(path) = arg_value(args, "--heightmap") {
+        terrain.heightmap = path.into();
+    }
+    if let Some(resolution) = parse_arg(args, "--terrain-resolution")? {
+        terrain.resolution = resolution;
+    }
+    if let Some(cell_size) = parse_arg(args, "--cell-size")? {
+        terrain.cell_size = cell_size;
+    }
+    if let Some(height_scale) = parse_arg(args, "--height-scale")? {
+        terrain.height_scale = height_scale;
+    }
+    Ok(terrain)
+}
 
-    /* ##########
-    SWAPCHAIN
-    ########## */
-    let caps = surface.capabilities(physical_device).unwrap();
-    let dimensions = caps.current_extent.unwrap_or([SCR_WIDTH as u32, SCR_HEIGHT as u32]);
-    let buffers_count = caps.min_image_count;
-    let (format, _color_space) = caps.supported_formats[0];
-    let usage = ImageUsage {
-        color_attachment: true,
-        .. ImageUsage::none()
-    };
-    let sharing_mode = SharingMode::Exclusive(present_queue.family().id());
-    let present_mode = {
-        let cap_present_modes = &caps.present_modes;
-        if cap_present_modes.immediate { vulkano::swapchain::PresentMode::Immediate }
-        else if cap_present_modes.mailbox { vulkano::swapchain::PresentMode::Mailbox }
-        else { vulkano::swapchain::PresentMode::Fifo }
-    };
-    println!("PresentMode: {:?}", present_mode);
-
-    // Create the swapchain and its buffers.
-    println!("Swapchain.");
-    let (swapchain, buffers) = Swapchain::new(
-        // Create the swapchain in this `device`'s memory.
-        device.clone(),
-        // The surface where the images will be presented.
-        surface,
-        // How many buffers to use in the swapchain.
-        buffers_count,
-        // The format of the images.
-        format,
-        // The size of each image.
-        dimensions,
-        // How many layers each image has.
-        1,
-        // What the images are going to be used for.
-        usage,
-        // Describes which queues will interact with the swapchain.
-        sharing_mode,
-        // What transformation to use with the surface.
-        vulkano::swapchain::SurfaceTransform::Identity,
-        // How to handle the alpha channel.
-        vulkano::swapchain::CompositeAlpha::Opaque,
-        // How to present images.
-        present_mode,
-        // Clip the parts of the buffer which aren't visible.
-        true,
-        // No previous swapchain.
-        None
-    ).unwrap();
+// Mouse options from `--mouse-sensitivity`, `--invert-y` and `--wheel speed|fov`.
+fn mouse_config(args: &[String]) -> Result<MouseConfig, VulrustError> {
+    let mut mouse = MouseConfig::default();
+    if let Some(sensitivity) = parse_arg(args, "--mouse-sensitivity")? {
+        mouse.sensitivity = cgmath::Rad(sensitivity);
+    }
+    mouse.invert_y = args.iter().any(|arg| arg == "--invert-y");
+    if let Some(wheel) = arg_value(args, "--wheel") {
+        mouse.wheel = WheelAction::from_name(wheel).ok_or_else(|| VulrustError::invalid_argument("--wheel", wheel))?;
+    }
+    Ok(mouse)
+}
 
-    /* ##########
-    RENDERPASS
-    ########## */
-    println!("Renderpass.");
-    let render_pass = Arc::new(single_pass_renderpass!(
-        device.clone(),
-        attachments: {
-            color: {
-                load: Clear,
-                store: Store,
-                format: swapchain.format(),
-                samples: 1,
+// Recompiles changed shader files and rebuilds the pipelines using them. Errors are printed
+// and leave the running shaders alone.
+fn reload_shaders(library: &mut ShaderLibrary, scene: &mut Scene) {
+    let mut changed = false;
+    for result in library.poll() {
+        match result {
+            Ok(path) => {
+                println!("Reloaded shader {}", path.display());
+                changed = true;
             },
-            depth: {
-                load: Clear,
-                store: DontCare,
-                format: vulkano::format::Format::D16Unorm,
-                samples: 1,
-            }
-        },
-        pass: {
-            color: [color],
-            depth_stencil: {depth}
+            Err(err) => println!("Shader error: {}", err)
         }
-    ).unwrap());
-
-    /* ##########
-    FRAMEBUFFERS
-    ########## */
-    println!("Framebuffers.");
-    let depth_buffer = vulkano::image::attachment::AttachmentImage::transient(device.clone(), dimensions, vulkano::format::D16Unorm).unwrap();
-    let framebuffers: Vec<Arc<Framebuffer<_,_>>> = buffers.iter().map(|buffer|
-        Arc::new(
-            Framebuffer::start(render_pass.clone())
-            .add(buffer.clone()).unwrap()
-            .add(depth_buffer.clone()).unwrap()
-            .build().unwrap()
-        )
-    ).collect();
-
-    let mut terrain_plane = GfxObjectHMap::new(device.clone(), render_pass.clone());
-    terrain_plane.create_plane_square(500, 0.15);
-
-    let mut cube = GfxObject3D::new(device.clone(), render_pass.clone());
-    cube.create_cube();
-
-    let mut rectangle = GfxObject::new(device.clone(), render_pass.clone());
-    rectangle.create_rectangle(1.0, 1.0);
-
-    let mut rectangle_instances: Vec<RectangleInstance> = Vec::new();
-    for _i in 0..100 {
-        rectangle_instances.push(RectangleInstanceBuilder::create(
-            [
-                -5.0 + rand::random::<f32>() * 10.0,
-                -5.0 + rand::random::<f32>() * 10.0,
-                10.0 - rand::random::<f32>() * 20.0
-            ],
-            [
-                rand::random::<f32>(),
-                rand::random::<f32>(),
-                rand::random::<f32>()
-            ]
-        ));
     }
-
-    let instances_buffer = CpuAccessibleBuffer::from_iter(
-        device.clone(),
-        BufferUsage::all(),
-        rectangle_instances.iter().map(|ri| {
-            ri.get_instance_vertex()
-        })
-    ).unwrap();
-
-    let mut world = World {
-        projection: perspective(Rad(1.4), SCR_WIDTH / SCR_HEIGHT, 0.01, 100.0).transpose(),
-        view: Matrix4::look_at(Point3::new(2.0, -6.0, 7.0), Point3::new(2.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0)).transpose(),
-        model: Matrix4::one(),
-        direction_angle: 0.0
-    };
-
-    let world_uniforms_buffer_pool = CpuBufferPool::new(device.clone(), BufferUsage::all());
-    let mut world_uniforms_buffer = world_uniforms_buffer_pool.next(
-        shader_utils::vs::ty::UniformMatrices {
-            projection: world.projection.into(),
-            view: world.view.into(),
-            model: world.model.transpose().into()
-        }
-    ).unwrap();
-
-    let mut world_uniforms_descriptor = Arc::new(
-        PersistentDescriptorSet::start(rectangle.get_pipeline(), 0)
-
-        .add_buffer(world_uniforms_buffer.clone())
-        .unwrap()
-
-        .build()
-        .unwrap()
-    );
-
-    let world_uniforms_buffer_pool_cube = CpuBufferPool::new(device.clone(), BufferUsage::all());
-    let mut world_uniforms_buffer_cube = world_uniforms_buffer_pool_cube.next(
-        shader_utils::vs_cube::ty::UniformMatrices {
-            projection: world.projection.into(),
-            view: world.view.into(),
-            model: world.model.transpose().into()
+    if changed {
+        for err in scene.reload_shaders(library) {
+            println!("Shader error: {}", err);
         }
-    ).unwrap();
-
-
-    let mut world_uniforms_descriptor_cube = Arc::new(
-        PersistentDescriptorSet::start(cube.get_pipeline(), 0)
-
-        .add_buffer(world_uniforms_buffer_cube.clone())
-        .unwrap()
-
-        .build()
-        .unwrap()
-    );
-
-    let mut world_uniforms_descriptor_terrain_plane = Arc::new(
-        PersistentDescriptorSet::start(terrain_plane.get_pipeline(), 0)
+    }
+}
 
-        .add_buffer(world_uniforms_buffer.clone())
-        .unwrap()
+fn main() {
+    if cfg!(target_os = "linux") {
+        avoid_winit_wayland_hack();
+    }
 
-        .build()
-        .unwrap()
-    );
+    let args: Vec<String> = std::env::args().collect();
+    if let Err(err) = run(&args) {
+        println!("Error: {}", err);
+        std::process::exit(1);
+    }
+}
 
-    let (image_sample, image_sample_future) = {
-        let _image_sample = load_image_sample();
-        let (w, h) = _image_sample.dimensions();
+fn run(args: &[String]) -> Result<(), VulrustError> {
+    let device = arg_value(args, "--gpu").map(DevicePreference::parse).unwrap_or_else(DevicePreference::from_env);
+    let validation = validation_mode(args)?;
+    let terrain = terrain_desc(args)?;
+    if args.iter().any(|arg| arg == "--headless") {
+        let scene_name = arg_value(args, "--scene").unwrap_or("all");
+        let options = HeadlessOptions {
+            path: arg_value(args, "--headless").unwrap_or("frame.png").to_string(),
+            dimensions: [SCR_WIDTH as u32, SCR_HEIGHT as u32],
+            parts: SceneParts::from_name(scene_name).ok_or_else(|| VulrustError::invalid_argument("--scene", scene_name))?,
+            terrain: terrain,
+            seed: parse_arg(args, "--seed")?.unwrap_or(0),
+            device: device,
+            validation: validation
+        };
+        return headless::render_to_png(&options);
+    }
 
-        ImmutableImage::from_iter(
-            _image_sample.into_raw().into_iter(),
-            vulkano::image::Dimensions::Dim2d { width: w, height: h },
-            vulkano::format::R8G8B8Srgb,
-            present_queue.clone()
-        ).unwrap()
+    // Everything else is read before opening the window, so a bad option fails right away.
+    let benchmark_frames: Option<usize> = parse_arg(args, "--benchmark")?;
+    let mouse = mouse_config(args)?;
+    let tick_rate = parse_arg_if(args, "--tick-rate", |&rate| game_loop::is_valid_rate(rate))?.unwrap_or(TICK_RATE);
+    let fps_cap = parse_arg(args, "--fps-cap")?;
+    let present_mode = present_mode(args, benchmark_frames.is_some())?;
+    let frames_in_flight = parse_arg_if(args, "--frames-in-flight", |&count| frames_in_flight::check_count(count).is_ok())?.unwrap_or(DEFAULT_FRAMES_IN_FLIGHT);
+    let mut events_loop = EventsLoop::new();
+    let config = RendererConfig {
+        title: "vulrust".to_string(),
+        width: SCR_WIDTH as u32,
+        height: SCR_HEIGHT as u32,
+        device: device,
+        validation: validation,
+        present_mode: present_mode,
+        frames_in_flight: frames_in_flight
     };
+    let mut renderer = Renderer::new(&config, &events_loop)?;
 
-    let sampler = vulkano::sampler::Sampler::new(
-            device.clone(),
-            vulkano::sampler::Filter::Linear,
-            vulkano::sampler::Filter::Linear,
-            vulkano::sampler::MipmapMode::Nearest,
-            vulkano::sampler::SamplerAddressMode::Repeat,
-            vulkano::sampler::SamplerAddressMode::Repeat,
-            vulkano::sampler::SamplerAddressMode::Repeat,
-            0.0, 1.0, 0.0, 0.0
-        ).unwrap();
-
-    let image_sample_descriptor = Arc::new(
-        PersistentDescriptorSet::start(terrain_plane.get_pipeline(), 1)
+    let uploads = Uploads::new(&renderer.queues);
+    let (mut scene, scene_future) = Scene::new(renderer.device.clone(), &uploads, renderer.render_pass.clone(), SceneParts::all(), &terrain, &mut rand::thread_rng())?;
+    renderer.join_future(scene_future);
 
-        .add_sampled_image(image_sample.clone(), sampler.clone())
-        .unwrap()
+    if let Some(path) = arg_value(args, "--gltf") {
+        let gltf = gltf_loader::load_gltf(path)?;
+        let gltf_future = scene.add_gltf(renderer.device.clone(), &uploads, renderer.render_pass.clone(), &gltf)?;
+        renderer.join_future(gltf_future);
+    }
 
-        .build()
-        .unwrap()
-    );
+    let mut world = World::new();
+    world.ground = scene.heightfield.take();
+    let mut dimensions = renderer.dimensions();
+    world.set_aspect_ratio(dimensions[0] as f32 / dimensions[1] as f32);
 
     /* ##########
     LOOP
     ########## */
     println!("Loop.");
-    // let mut previous_frame_end = Box::new(now(device.clone())) as Box<GpuFuture>;
-    let mut previous_frame_end = Box::new(image_sample_future) as Box<GpuFuture>;
     let mut frame_counter = 1;
-    let start_time = time::SteadyTime::now();
-
-    let current_viewport = Some(vec![Viewport {
-        origin: [0.0, 0.0],
-        dimensions: [dimensions[0] as f32, dimensions[1] as f32],
-        depth_range: 0.0 .. 1.0,
-    }]);
-
-    let dynamic_state = DynamicState {
-        line_width: None,
-        viewports: current_viewport.clone(),
-        scissors: None,
-    };
+    let start_time = Instant::now();
 
-    let mut delta: f32 = 0.0;
-    let delta_uniform_pool = CpuBufferPool::new(device.clone(), BufferUsage::all());
-    let mut world_updated = false;
-    let mut pressed_keys: Vec<Option<VirtualKeyCode>> = Vec::new();
+    let input_map = match arg_value(args, "--input-config") {
+        Some(path) => InputMap::load(path)?,
+        None => InputMap::default()
+    };
+    let input = InputState::new(input_map);
+    let mut cursor_captured = false;
+    let mut shader_library = arg_value(args, "--shader-dir").map(ShaderLibrary::new);
 
-    loop {
-        previous_frame_end.cleanup_finished();
-        std::thread::sleep(std::time::Duration::from_millis(10));
+    let mut game = Game {
+        world: world,
+        input: input
+    };
+    let mut game_loop = GameLoop::new(tick_rate)?;
+    game_loop.timer.set_fps_cap(fps_cap);
 
-        let (index, acq_future) = vulkano::swapchain::acquire_next_image(swapchain.clone(), None).unwrap();
+    let mut frame_stats = FrameStats::new(WARMUP_FRAMES);
+    let mut failure = None;
+    game_loop.run(&mut game, update, |game, frame| {
+        let world = &mut game.world;
+        let input = &mut game.input;
 
-        let c_color = [
+        renderer.clear_color = [
             1.0 * (frame_counter as f32 % 1200.0 / 1200.0),
             1.0 * (frame_counter as f32 % 120.0 / 120.0),
-            1.0 * (frame_counter as f32 % 2000.0 / 2000.0)
-        ].into();
-
-        delta += 2.0;
-        let delta_buffer = delta_uniform_pool.next(shader_utils::vs::ty::DeltaUniform {
-            delta: (delta % 630.0) / 100.0
-        }).unwrap();
-        let delta_descriptor_set = Arc::new(
-            PersistentDescriptorSet::start(rectangle.get_pipeline(), 1)
-
-            .add_buffer(delta_buffer)
-            .unwrap()
-
-            .build()
-            .unwrap()
-        );
-
-        let mut command_buffer_builder = AutoCommandBufferBuilder::primary_one_time_submit(device.clone(), present_queue.family()).unwrap()
-            .begin_render_pass(framebuffers[index].clone(), false, vec![c_color, 1f32.into()])
-            .unwrap();
-
-
-        command_buffer_builder = command_buffer_builder.draw(
-            rectangle.get_pipeline(),
-            &dynamic_state,
-            (rectangle.get_vertex_buffer(), instances_buffer.clone()),
-            (world_uniforms_descriptor.clone(), delta_descriptor_set.clone()),
-            ()
-        ).unwrap();
-
-        command_buffer_builder = command_buffer_builder.draw(
-            cube.get_pipeline(),
-            &dynamic_state,
-            cube.get_vertex_buffer(),
-            world_uniforms_descriptor_cube.clone(),
-            ()
-        ).unwrap();
-
-        command_buffer_builder = command_buffer_builder.draw(
-            terrain_plane.get_pipeline(),
-            &dynamic_state,
-            terrain_plane.get_vertex_buffer(),
-            (world_uniforms_descriptor_terrain_plane.clone(), image_sample_descriptor.clone()),
-            ()
-        ).unwrap();
-
-
-        let command_buffer = command_buffer_builder
-            .end_render_pass()
-            .unwrap()
-
-            .build()
-            .unwrap();
-
-        let future = previous_frame_end.join(acq_future)
-            .then_execute(present_queue.clone(), command_buffer).unwrap()
-            .then_swapchain_present(present_queue.clone(), swapchain.clone(), index)
-            .then_signal_fence_and_flush().unwrap();
-
-        previous_frame_end = Box::new(future) as Box<_>;
+            1.0 * (frame_counter as f32 % 2000.0 / 2000.0),
+            1.0
+        ];
+
+        if let Some(ref mut library) = shader_library {
+            if frame_counter % SHADER_POLL_FRAMES == 1 {
+                reload_shaders(library, &mut scene);
+            }
+        }
+
+        world.alpha = frame.alpha;
+        let draw_start = Instant::now();
+        if let Err(err) = renderer.draw_frame(world, &scene.drawables) {
+            failure = Some(err);
+            return false;
+        }
+        frame_stats.record(draw_start.elapsed());
+        if benchmark_frames.is_some_and(|frames| frame_stats.len() >= frames) {
+            return false;
+        }
 
         // Handling the window events in order to close the program when the user wants to close
         // it.
         let mut done = false;
+        let mut resized = false;
         events_loop.poll_events(|ev| {
             match ev {
                 winit::Event::WindowEvent { event, .. } => {
+                    input.handle_window_event(&event);
                     match event {
-                        winit::WindowEvent::KeyboardInput { input, .. } => {
-                            println!("{:?}", input);
-                            match input.state {
-                                winit::ElementState::Pressed => {
-                                    if !pressed_keys.contains(&input.virtual_keycode) {
-                                        pressed_keys.push(input.virtual_keycode);
-                                    }
-                                },
-                                winit::ElementState::Released => {
-                                    pressed_keys.retain(|&code| code != input.virtual_keycode)
-                                }
-                            }
-                        }
+                        winit::WindowEvent::MouseWheel { delta, .. } => {
+                            let lines = match delta {
+                                winit::MouseScrollDelta::LineDelta(_, y) => y,
+                                winit::MouseScrollDelta::PixelDelta(position) => position.y as f32 / PIXELS_PER_LINE
+                            };
+                            mouse.scroll(world, lines);
+                        },
+                        // Don't keep the cursor locked in a window the user switched away from.
+                        winit::WindowEvent::Focused(false) if cursor_captured => {
+                            cursor_captured = false;
+                            renderer.capture_cursor(false);
+                        },
+                        winit::WindowEvent::Resized(_) => resized = true,
                         winit::WindowEvent::CloseRequested => done = true,
                         _ => ()
                     }
                 },
+                winit::Event::DeviceEvent { event: winit::DeviceEvent::MouseMotion { delta }, .. } if cursor_captured => {
+                    mouse.look(world, delta.0, delta.1);
+                },
                 _ => ()
             }
         });
-        if done { break; }
+        if done { return false; }
 
-        for key in pressed_keys.iter() {
-            match key {
-                Some(VirtualKeyCode::Up) => {
-                    world.move_forwards();
-                    world_updated = true;
-                },
-                Some(VirtualKeyCode::Down) => {
-                    world.move_backwards();
-                    world_updated = true;
-                },
-                Some(VirtualKeyCode::Right) => {
-                    world.rotate_clockwise();
-                    world_updated = true;
-                },
-                Some(VirtualKeyCode::Left) => {
-                    world.rotate_counterclockwise();
-                    world_updated = true;
-                },
-                _ => ()
+        if resized {
+            if let Err(err) = renderer.resize() {
+                failure = Some(err);
+                return false;
             }
         }
 
-        if world_updated {
-            world_updated = false;
-
-            world_uniforms_buffer = world_uniforms_buffer_pool.next(
-                shader_utils::vs::ty::UniformMatrices {
-                    projection: world.projection.into(),
-                    view: world.view.into(),
-                    model: world.model.transpose().into()
-                }
-            ).unwrap();
-
-            world_uniforms_descriptor = Arc::new(
-                PersistentDescriptorSet::start(rectangle.get_pipeline(), 0)
-
-                .add_buffer(world_uniforms_buffer.clone())
-                .unwrap()
-
-                .build()
-                .unwrap()
-            );
-
-            world_uniforms_buffer_cube = world_uniforms_buffer_pool_cube.next(
-                shader_utils::vs_cube::ty::UniformMatrices {
-                    projection: world.projection.into(),
-                    view: world.view.into(),
-                    model: world.model.transpose().into()
-                }
-            ).unwrap();
-
-
-            world_uniforms_descriptor_cube = Arc::new(
-                PersistentDescriptorSet::start(cube.get_pipeline(), 0)
-
-                .add_buffer(world_uniforms_buffer_cube.clone())
-                .unwrap()
-
-                .build()
-                .unwrap()
-            );
-
-            world_uniforms_descriptor_terrain_plane = Arc::new(
-                PersistentDescriptorSet::start(terrain_plane.get_pipeline(), 0)
-
-                .add_buffer(world_uniforms_buffer.clone())
-                .unwrap()
+        // The swapchain may also get recreated inside `draw_frame` when it goes out of date.
+        if renderer.dimensions() != dimensions {
+            dimensions = renderer.dimensions();
+            world.set_aspect_ratio(dimensions[0] as f32 / dimensions[1] as f32);
+        }
 
-                .build()
-                .unwrap()
-            );
+        // Toggles are checked every frame, so presses between two updates aren't lost.
+        if input.pressed(Action::ToggleWalking) {
+            world.toggle_walking();
+            println!("Walking: {}", world.walking);
+        }
+        if input.pressed(Action::ToggleCursor) {
+            cursor_captured = !cursor_captured;
+            renderer.capture_cursor(cursor_captured);
         }
+        if input.pressed(Action::CyclePresentMode) {
+            let preference = renderer.present_preference().next();
+            renderer.set_present_mode(preference);
+        }
+        input.end_frame();
 
-        // println!("Frame #{:?}", frame_counter);
         frame_counter += 1;
+        true
+    });
+
+    // Runs shorter than a timer tick have no frame rate to speak of.
+    let elapsed = start_time.elapsed().as_secs_f64();
+    if elapsed > 0.0 {
+        println!("Average FPS: {:.1}", frame_counter as f64 / elapsed);
+    }
+    if benchmark_frames.is_some() {
+        println!("Benchmark: {} frames in flight, {:?}", renderer.frames_in_flight(), renderer.present_mode());
+    }
+    if !frame_stats.is_empty() {
+        println!("{}", frame_stats);
     }
 
-    let avg_fps = frame_counter / (time::SteadyTime::now() - start_time).num_seconds();
-    println!("Average FPS: {}", avg_fps);
+    match failure {
+        Some(err) => Err(err),
+        None => renderer.validation.check()
+    }
 }
@@ -24,3 +24,45 @@ pub fn ortho(w: f32, h: f32) -> Matrix4<f32> {
         1.0
     )
 }
+
+// Maps OpenGL clip space, what `cgmath::perspective` produces, to Vulkan's: y points down
+// and depth goes from 0 to 1 instead of -1 to 1.
+pub fn vulkan_clip() -> Matrix4<f32> {
+    Matrix4::new(
+        1.0,
+        0.0,
+        0.0,
+        0.0,
+
+        0.0,
+        -1.0,
+        0.0,
+        0.0,
+
+        0.0,
+        0.0,
+        0.5,
+        0.0,
+
+        0.0,
+        0.0,
+        0.5,
+        1.0
+    )
+}
+
+// Unit normal of the counter-clockwise triangle `a`, `b`, `c`, up for degenerate ones.
+pub fn face_normal(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> [f32; 3] {
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    let n = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0]
+    ];
+    let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+    if length == 0.0 {
+        return [0.0, 1.0, 0.0];
+    }
+    [n[0] / length, n[1] / length, n[2] / length]
+}
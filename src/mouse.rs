@@ -0,0 +1,57 @@
+use cgmath::Rad;
+
+use world::World;
+
+
+// What the scroll wheel changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WheelAction {
+    MoveSpeed,
+    Fov
+}
+
+impl WheelAction {
+    pub fn from_name(name: &str) -> Option<WheelAction> {
+        match name {
+            "speed" => Some(WheelAction::MoveSpeed),
+            "fov" => Some(WheelAction::Fov),
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MouseConfig {
+    // Camera rotation per unit of `DeviceEvent::MouseMotion`, roughly a pixel.
+    pub sensitivity: Rad<f32>,
+    // Moving the mouse forwards looks down instead of up.
+    pub invert_y: bool,
+    pub wheel: WheelAction
+}
+
+impl Default for MouseConfig {
+    fn default() -> MouseConfig {
+        MouseConfig {
+            sensitivity: Rad(0.003),
+            invert_y: false,
+            wheel: WheelAction::MoveSpeed
+        }
+    }
+}
+
+impl MouseConfig {
+    // Turns the camera by a raw mouse motion, `dy` grows as the mouse is pulled back.
+    pub fn look(&self, world: &mut World, dx: f64, dy: f64) {
+        let dy = if self.invert_y { -dy } else { dy };
+        world.camera.turn(self.sensitivity * dx as f32);
+        world.camera.look_up(self.sensitivity * -dy as f32);
+    }
+
+    // `lines` is positive when scrolling up, away from the user.
+    pub fn scroll(&self, world: &mut World, lines: f32) {
+        match self.wheel {
+            WheelAction::MoveSpeed => world.scale_move_speed(lines),
+            WheelAction::Fov => world.zoom(lines)
+        }
+    }
+}
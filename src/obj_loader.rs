@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use math_utils::face_normal;
+use vertex_types::{Vertex3DNormal3D, Vertex3DNormal3DUV};
+
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io(PathBuf, io::Error),
+    Parse { path: PathBuf, line: usize, message: String }
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ObjError::Io(ref path, ref err) => write!(f, "{}: {}", path.display(), err),
+            ObjError::Parse { ref path, line, ref message } => write!(f, "{}:{}: {}", path.display(), line, message)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjMaterial {
+    pub name: String,
+    pub diffuse: [f32; 3]
+}
+
+// One `o`/`g` section of the file, already triangulated into a plain triangle list.
+#[derive(Debug, Clone)]
+pub struct ObjMesh {
+    pub name: String,
+    pub material: Option<String>,
+    pub vertices: Vec<Vertex3DNormal3DUV>
+}
+
+impl ObjMesh {
+    pub fn to_vertex3d_normal3d(&self) -> Vec<Vertex3DNormal3D> {
+        self.vertices.iter().map(|v| Vertex3DNormal3D {
+            position: v.position,
+            normal: v.normal
+        }).collect()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ObjModel {
+    pub meshes: Vec<ObjMesh>,
+    pub materials: HashMap<String, ObjMaterial>
+}
+
+impl ObjModel {
+    pub fn diffuse(&self, mesh: &ObjMesh) -> Option<[f32; 3]> {
+        mesh.material.as_ref()
+            .and_then(|name| self.materials.get(name))
+            .map(|material| material.diffuse)
+    }
+}
+
+pub fn load_obj<P: AsRef<Path>>(path: P) -> Result<ObjModel, ObjError> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path).map_err(|err| ObjError::Io(path.to_path_buf(), err))?;
+    parse_obj(&source, path)
+}
+
+// Parses OBJ source, `path` is used for error messages and to resolve `mtllib` files.
+pub fn parse_obj(source: &str, path: &Path) -> Result<ObjModel, ObjError> {
+    let mut parser = ObjParser {
+        path: path,
+        line: 0,
+        positions: Vec::new(),
+        uvs: Vec::new(),
+        normals: Vec::new(),
+        model: ObjModel::default()
+    };
+    let mut name = String::from("default");
+    let mut material: Option<String> = None;
+    let mut vertices: Vec<Vertex3DNormal3DUV> = Vec::new();
+    // Keywords already warned about, each is only reported once.
+    let mut skipped: Vec<&str> = Vec::new();
+
+    for (i, line) in source.lines().enumerate() {
+        parser.line = i + 1;
+        let line = match line.find('#') {
+            Some(comment) => &line[..comment],
+            None => line
+        };
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => {
+                let v = parser.floats(&args, 3)?;
+                parser.positions.push([v[0], v[1], v[2]]);
+            },
+            "vt" => {
+                // u with optional v and w, w is dropped.
+                let v = parser.floats(&args, args.len().clamp(1, 3))?;
+                parser.uvs.push([v[0], v.get(1).cloned().unwrap_or(0.0)]);
+            },
+            "vn" => {
+                let v = parser.floats(&args, 3)?;
+                parser.normals.push([v[0], v[1], v[2]]);
+            },
+            "f" => {
+                if args.len() < 3 {
+                    return Err(parser.error(format!("face needs at least 3 vertices, got {}", args.len())));
+                }
+                let corners = args.iter()
+                    .map(|arg| parser.corner(arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                // Triangle fan around the first corner, fine for the convex polygons exporters write.
+                for k in 1 .. corners.len() - 1 {
+                    parser.push_triangle(&mut vertices, [&corners[0], &corners[k], &corners[k + 1]]);
+                }
+            },
+            "o" | "g" => {
+                parser.finish_mesh(&name, &material, &mut vertices);
+                name = if args.is_empty() { String::from("default") } else { args.join(" ") };
+            },
+            "usemtl" => {
+                if args.is_empty() {
+                    return Err(parser.error(String::from("usemtl without a material name")));
+                }
+                if material.as_deref() != Some(args[0]) {
+                    parser.finish_mesh(&name, &material, &mut vertices);
+                }
+                material = Some(args[0].to_string());
+            },
+            "mtllib" => {
+                for lib in args.iter() {
+                    let mtl_path = path.parent().unwrap_or(Path::new("")).join(lib);
+                    let materials = load_mtl(&mtl_path)?;
+                    parser.model.materials.extend(materials);
+                }
+            },
+            // Smoothing groups and the rest of the format don't matter for rendering.
+            "s" | "l" | "p" => (),
+            // Free-form geometry, merging groups and vendor extensions are skipped too.
+            _ => {
+                if !skipped.contains(&keyword) {
+                    println!("{}:{}: skipping unknown keyword {:?}", path.display(), parser.line, keyword);
+                    skipped.push(keyword);
+                }
+            }
+        }
+    }
+    parser.finish_mesh(&name, &material, &mut vertices);
+
+    Ok(parser.model)
+}
+
+pub fn load_mtl<P: AsRef<Path>>(path: P) -> Result<HashMap<String, ObjMaterial>, ObjError> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path).map_err(|err| ObjError::Io(path.to_path_buf(), err))?;
+    let mut materials = HashMap::new();
+    let mut current: Option<ObjMaterial> = None;
+
+    for (i, line) in source.lines().enumerate() {
+        let error = |message: String| ObjError::Parse { path: path.to_path_buf(), line: i + 1, message: message };
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("newmtl") => {
+                if let Some(material) = current.take() {
+                    materials.insert(material.name.clone(), material);
+                }
+                let name = tokens.next().ok_or_else(|| error(String::from("newmtl without a name")))?;
+                current = Some(ObjMaterial { name: name.to_string(), diffuse: [1.0, 1.0, 1.0] });
+            },
+            Some("Kd") => {
+                let material = current.as_mut().ok_or_else(|| error(String::from("Kd before newmtl")))?;
+                let values = tokens
+                    .map(|token| token.parse::<f32>().map_err(|_| error(format!("invalid number {:?}", token))))
+                    .collect::<Result<Vec<f32>, _>>()?;
+                if values.len() != 3 {
+                    return Err(error(format!("Kd needs 3 values, got {}", values.len())));
+                }
+                material.diffuse = [values[0], values[1], values[2]];
+            },
+            // Only the diffuse colour is used, skip everything else.
+            _ => ()
+        }
+    }
+    if let Some(material) = current.take() {
+        materials.insert(material.name.clone(), material);
+    }
+
+    Ok(materials)
+}
+
+// Resolved 0-based `v/vt/vn` indices of one face corner.
+struct Corner {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>
+}
+
+struct ObjParser<'a> {
+    path: &'a Path,
+    line: usize,
+    positions: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    normals: Vec<[f32; 3]>,
+    model: ObjModel
+}
+
+impl<'a> ObjParser<'a> {
+    fn error(&self, message: String) -> ObjError {
+        ObjError::Parse { path: self.path.to_path_buf(), line: self.line, message: message }
+    }
+
+    // Parses at least `count` floats, extra values (like the optional `w`) are ignored.
+    fn floats(&self, args: &[&str], count: usize) -> Result<Vec<f32>, ObjError> {
+        if args.len() < count {
+            return Err(self.error(format!("expected {} values, got {}", count, args.len())));
+        }
+        args.iter().take(count)
+            .map(|arg| arg.parse::<f32>().map_err(|_| self.error(format!("invalid number {:?}", arg))))
+            .collect()
+    }
+
+    // OBJ indices are 1-based, negative ones count back from the last element read so far.
+    fn index(&self, token: &str, len: usize, what: &str) -> Result<usize, ObjError> {
+        let index = token.parse::<i64>().map_err(|_| self.error(format!("invalid {} index {:?}", what, token)))?;
+        let resolved = if index < 0 { len as i64 + index } else { index - 1 };
+        if index == 0 || resolved < 0 || resolved >= len as i64 {
+            return Err(self.error(format!("{} index {} out of range", what, index)));
+        }
+        Ok(resolved as usize)
+    }
+
+    fn corner(&self, token: &str) -> Result<Corner, ObjError> {
+        let mut parts = token.split('/');
+        let position = self.index(parts.next().unwrap_or(""), self.positions.len(), "vertex")?;
+        let uv = match parts.next() {
+            Some("") | None => None,
+            Some(uv) => Some(self.index(uv, self.uvs.len(), "texture")?)
+        };
+        let normal = match parts.next() {
+            Some("") | None => None,
+            Some(normal) => Some(self.index(normal, self.normals.len(), "normal")?)
+        };
+        if parts.next().is_some() {
+            return Err(self.error(format!("invalid face vertex {:?}", token)));
+        }
+        Ok(Corner { position: position, uv: uv, normal: normal })
+    }
+
+    fn push_triangle(&self, vertices: &mut Vec<Vertex3DNormal3DUV>, corners: [&Corner; 3]) {
+        let p: Vec<[f32; 3]> = corners.iter().map(|c| self.positions[c.position]).collect();
+        let face_normal = face_normal(p[0], p[1], p[2]);
+
+        for (corner, position) in corners.iter().zip(p) {
+            vertices.push(Vertex3DNormal3DUV {
+                position: position,
+                normal: corner.normal.map(|n| self.normals[n]).unwrap_or(face_normal),
+                uv: corner.uv.map(|t| self.uvs[t]).unwrap_or([0.0, 0.0])
+            });
+        }
+    }
+
+    fn finish_mesh(&mut self, name: &str, material: &Option<String>, vertices: &mut Vec<Vertex3DNormal3DUV>) {
+        if vertices.is_empty() {
+            return;
+        }
+        self.model.meshes.push(ObjMesh {
+            name: name.to_string(),
+            material: material.clone(),
+            vertices: vertices.split_off(0)
+        });
+    }
+}
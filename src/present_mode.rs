@@ -0,0 +1,95 @@
+use std::env;
+use std::fmt;
+
+use vulkano::format::Format;
+use vulkano::swapchain::{ColorSpace, PresentMode, SupportedPresentModes};
+
+use error::VulrustError;
+
+
+pub const PRESENT_MODE_ENV_VAR: &str = "VULRUST_PRESENT_MODE";
+
+// What `--present-mode` or `VULRUST_PRESENT_MODE` asks for. Surfaces don't have to support
+// anything but Fifo, so each one falls back to the closest mode that is there.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PresentPreference {
+    // Vsync, frames queue up behind the display. The default.
+    #[default]
+    Fifo,
+    // Vsync, but a late frame is shown right away and may tear.
+    FifoRelaxed,
+    // Vsync without blocking, the newest frame replaces a waiting one.
+    Mailbox,
+    // No vsync, tears.
+    Immediate
+}
+
+const PREFERENCES: [PresentPreference; 4] = [
+    PresentPreference::Fifo,
+    PresentPreference::FifoRelaxed,
+    PresentPreference::Mailbox,
+    PresentPreference::Immediate
+];
+
+impl PresentPreference {
+    // The names in `Display`, plus `vsync` and `novsync` for Fifo and Immediate.
+    pub fn parse(value: &str) -> Option<PresentPreference> {
+        match value.trim().to_lowercase().as_str() {
+            "vsync" => Some(PresentPreference::Fifo),
+            "novsync" => Some(PresentPreference::Immediate),
+            value => PREFERENCES.iter().cloned().find(|preference| preference.to_string() == value)
+        }
+    }
+
+    // The default when `VULRUST_PRESENT_MODE` isn't set.
+    pub fn from_env() -> Result<PresentPreference, VulrustError> {
+        match env::var(PRESENT_MODE_ENV_VAR) {
+            Ok(value) => PresentPreference::parse(&value).ok_or_else(|| VulrustError::invalid_argument(PRESENT_MODE_ENV_VAR, &value)),
+            Err(_) => Ok(PresentPreference::default())
+        }
+    }
+
+    // The one after this, for switching modes with a key.
+    pub fn next(self) -> PresentPreference {
+        let index = PREFERENCES.iter().position(|&preference| preference == self).unwrap_or(0);
+        PREFERENCES[(index + 1) % PREFERENCES.len()]
+    }
+
+    // Modes to try in order. Fifo comes last because every surface supports it.
+    pub fn fallbacks(self) -> &'static [PresentMode] {
+        match self {
+            PresentPreference::Fifo => &[PresentMode::Fifo],
+            PresentPreference::FifoRelaxed => &[PresentMode::Relaxed, PresentMode::Fifo],
+            PresentPreference::Mailbox => &[PresentMode::Mailbox, PresentMode::Fifo],
+            PresentPreference::Immediate => &[PresentMode::Immediate, PresentMode::Mailbox, PresentMode::Fifo]
+        }
+    }
+
+    pub fn choose(self, supported: &SupportedPresentModes) -> PresentMode {
+        self.fallbacks().iter().cloned().find(|&mode| supported.supports(mode)).unwrap_or(PresentMode::Fifo)
+    }
+}
+
+impl fmt::Display for PresentPreference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            PresentPreference::Fifo => "fifo",
+            PresentPreference::FifoRelaxed => "fifo-relaxed",
+            PresentPreference::Mailbox => "mailbox",
+            PresentPreference::Immediate => "immediate"
+        };
+        write!(f, "{}", name)
+    }
+}
+
+pub fn is_srgb(format: Format) -> bool {
+    matches!(format, Format::B8G8R8A8Srgb | Format::R8G8B8A8Srgb | Format::A8B8G8R8SrgbPack32)
+}
+
+// An sRGB swapchain format, so the shaders' linear output gets gamma encoded when written.
+// Falls back to whatever the surface lists first.
+pub fn choose_surface_format(supported: &[(Format, ColorSpace)]) -> Option<(Format, ColorSpace)> {
+    supported.iter().cloned()
+        .find(|&(format, color_space)| is_srgb(format) && color_space == ColorSpace::SrgbNonLinear)
+        .or_else(|| supported.first().cloned())
+}
@@ -1,3 +1,4 @@
+use error::VulrustError;
 use vertex_types::Vertex3DColor3D;
 
 
@@ -31,11 +32,11 @@ impl RectangleInstance {
         self.instance_vertex = Some(instance_vertex);
     }
 
-    pub fn get_instance_vertex(&self) -> Vertex3DColor3D
+    pub fn get_instance_vertex(&self) -> Result<Vertex3DColor3D, VulrustError>
     {
         match self.instance_vertex {
-            Some(ref instance_vertex) => { instance_vertex.clone() }
-            None => { panic!("Empty instance vector!") }
+            Some(ref instance_vertex) => Ok(instance_vertex.clone()),
+            None => Err(VulrustError::Missing("instance vertex"))
         }
     }
 }
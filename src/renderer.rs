@@ -0,0 +1,392 @@
+use std::sync::Arc;
+use vulkano::command_buffer::AutoCommandBufferBuilder;
+use vulkano::command_buffer::DynamicState;
+use vulkano::device::Device;
+use vulkano::format::Format;
+use vulkano::framebuffer::Framebuffer;
+use vulkano::framebuffer::FramebufferAbstract;
+use vulkano::framebuffer::RenderPassAbstract;
+use vulkano::image::ImageUsage;
+use vulkano::image::SwapchainImage;
+use vulkano::image::attachment::AttachmentImage;
+use vulkano::instance::Instance;
+use vulkano::instance::InstanceExtensions;
+use vulkano::pipeline::viewport::Viewport;
+use vulkano::swapchain::AcquireError;
+use vulkano::swapchain::PresentMode;
+use vulkano::swapchain::Surface;
+use vulkano::swapchain::Swapchain;
+use vulkano::swapchain::SwapchainCreationError;
+use vulkano::sync::FlushError;
+use vulkano::sync::GpuFuture;
+use vulkano_win::VkSurfaceBuild;
+use winit::EventsLoop;
+use winit::Window;
+use winit::WindowBuilder;
+use winit::dpi::LogicalSize;
+
+use device_selection;
+use device_selection::{DevicePreference, DeviceRequirements, Queues};
+use drawable;
+use drawable::{Drawable, FrameUniforms};
+use error::VulrustError;
+use frames_in_flight::FramesInFlight;
+use present_mode;
+use present_mode::PresentPreference;
+use validation;
+use validation::{Validation, ValidationMode};
+use world::World;
+
+
+pub struct RendererConfig {
+    pub title: String,
+    pub width: u32,
+    pub height: u32,
+    pub device: DevicePreference,
+    pub validation: ValidationMode,
+    pub present_mode: PresentPreference,
+    // How many frames the CPU may record ahead of the GPU, at least 1.
+    pub frames_in_flight: usize
+}
+
+pub struct Renderer {
+    pub instance: Arc<Instance>,
+    // Debug callback of `--validation`, see `validation::create_instance`.
+    pub validation: Validation,
+    pub device: Arc<Device>,
+    // Drawing, presenting and uploads, see `device_selection::QueueFamilies`.
+    pub queues: Queues,
+    pub surface: Arc<Surface<Window>>,
+    pub render_pass: Arc<RenderPassAbstract + Send + Sync>,
+    pub clear_color: [f32; 4],
+    present_preference: PresentPreference,
+    // What `present_preference` came down to on this surface.
+    present_mode: PresentMode,
+    swapchain: Arc<Swapchain<Window>>,
+    framebuffers: Vec<Arc<FramebufferAbstract + Send + Sync>>,
+    dynamic_state: DynamicState,
+    frames: FramesInFlight,
+    recreate_swapchain: bool,
+    previous_frame_end: Option<Box<GpuFuture + Send + Sync>>
+}
+
+impl Renderer {
+    pub fn new(config: &RendererConfig, events_loop: &EventsLoop) -> Result<Renderer, VulrustError> {
+        /* ##########
+        INSTANCE
+        ########## */
+        println!("Instance.");
+        let instance_extensions = InstanceExtensions::supported_by_core()?;
+        let (instance, validation) = validation::create_instance(instance_extensions, config.validation)?;
+
+        /* ##########
+        WINDOW
+        ########## */
+        println!("Window.");
+        let window_builder = WindowBuilder::new()
+            .with_title(config.title.clone())
+            .with_dimensions(LogicalSize::new(config.width as f64, config.height as f64));
+        let surface = window_builder.build_vk_surface(events_loop, instance.clone())?;
+
+        /* ##########
+        PHYSICAL DEVICE
+        ########## */
+        println!("Physical device.");
+        let requirements = DeviceRequirements::windowed();
+        let (physical_device, queue_families) = device_selection::select_device(&instance, &requirements, Some(&surface), &config.device)?;
+
+        /* ##########
+        DEVICE
+        ########## */
+        println!("Device.");
+        let (device, queues) = device_selection::create_device(physical_device, &requirements, queue_families)?;
+
+        /* ##########
+        SWAPCHAIN
+        ########## */
+        let caps = surface.capabilities(physical_device)?;
+        let dimensions = caps.current_extent.unwrap_or([config.width, config.height]);
+        let (format, color_space) = present_mode::choose_surface_format(&caps.supported_formats).ok_or(VulrustError::SurfaceUnsupported)?;
+        println!("Format: {:?} {:?}", format, color_space);
+
+        println!("Swapchain.");
+        let (swapchain, buffers, present_mode) = build_swapchain(device.clone(), surface.clone(), &queues, format, dimensions, config.present_mode, None)?;
+
+        /* ##########
+        RENDERPASS
+        ########## */
+        println!("Renderpass.");
+        let render_pass = Arc::new(single_pass_renderpass!(
+            device.clone(),
+            attachments: {
+                color: {
+                    load: Clear,
+                    store: Store,
+                    format: swapchain.format(),
+                    samples: 1,
+                },
+                depth: {
+                    load: Clear,
+                    store: DontCare,
+                    format: Format::D16Unorm,
+                    samples: 1,
+                }
+            },
+            pass: {
+                color: [color],
+                depth_stencil: {depth}
+            }
+        )?) as Arc<RenderPassAbstract + Send + Sync>;
+
+        /* ##########
+        FRAMEBUFFERS
+        ########## */
+        println!("Framebuffers.");
+        let framebuffers = build_framebuffers(device.clone(), render_pass.clone(), &buffers)?;
+
+        let dynamic_state = DynamicState {
+            line_width: None,
+            viewports: Some(vec![viewport(dimensions)]),
+            scissors: None,
+        };
+
+        Ok(Renderer {
+            instance: instance.clone(),
+            validation: validation,
+            device: device.clone(),
+            queues: queues,
+            surface: surface,
+            render_pass: render_pass,
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+            present_preference: config.present_mode,
+            present_mode: present_mode,
+            swapchain: swapchain,
+            framebuffers: framebuffers,
+            dynamic_state: dynamic_state,
+            frames: FramesInFlight::new(device.clone(), config.frames_in_flight)?,
+            recreate_swapchain: false,
+            previous_frame_end: Some(Box::new(vulkano::sync::now(device.clone())) as Box<GpuFuture + Send + Sync>)
+        })
+    }
+
+    pub fn dimensions(&self) -> [u32; 2] {
+        self.swapchain.dimensions()
+    }
+
+    pub fn present_preference(&self) -> PresentPreference {
+        self.present_preference
+    }
+
+    pub fn present_mode(&self) -> PresentMode {
+        self.present_mode
+    }
+
+    pub fn frames_in_flight(&self) -> usize {
+        self.frames.len()
+    }
+
+    // Switches vsync on or off. The swapchain is recreated before the next frame.
+    pub fn set_present_mode(&mut self, preference: PresentPreference) {
+        self.present_preference = preference;
+        self.recreate_swapchain = true;
+    }
+
+    // Grabs and hides the cursor for mouse look, or gives it back.
+    pub fn capture_cursor(&self, capture: bool) {
+        let window = self.surface.window();
+        if let Err(err) = window.grab_cursor(capture) {
+            println!("Couldn't grab cursor: {}", err);
+        }
+        window.hide_cursor(capture);
+    }
+
+    // Makes the next frame wait for `future`, e.g. the texture uploads of a freshly built scene.
+    pub fn join_future(&mut self, future: Box<GpuFuture + Send + Sync>) {
+        let previous_frame_end = self.take_previous_frame_end();
+        self.previous_frame_end = Some(Box::new(previous_frame_end.join(future)) as Box<GpuFuture + Send + Sync>);
+    }
+
+    // What the next frame has to wait for. A frame that failed halfway leaves nothing, then
+    // the next one just starts from now.
+    fn take_previous_frame_end(&mut self) -> Box<GpuFuture + Send + Sync> {
+        match self.previous_frame_end.take() {
+            Some(previous_frame_end) => previous_frame_end,
+            None => Box::new(vulkano::sync::now(self.device.clone())) as Box<GpuFuture + Send + Sync>
+        }
+    }
+
+    // Recreates the swapchain and everything sized after it to match the current window size.
+    // Call it on `WindowEvent::Resized`, out-of-date swapchains are handled by `draw_frame` itself.
+    pub fn resize(&mut self) -> Result<(), VulrustError> {
+        self.recreate_swapchain = true;
+        self.recreate_swapchain()
+    }
+
+    fn recreate_swapchain(&mut self) -> Result<(), VulrustError> {
+        let dimensions = {
+            let window = self.surface.window();
+            match window.get_inner_size() {
+                Some(size) => {
+                    let size: (u32, u32) = size.to_physical(window.get_hidpi_factor()).into();
+                    [size.0, size.1]
+                },
+                // The window is gone or minimized, try again on the next frame.
+                None => return Ok(())
+            }
+        };
+
+        let format = self.swapchain.format();
+        let (swapchain, buffers, present_mode) = match build_swapchain(self.device.clone(), self.surface.clone(), &self.queues, format, dimensions, self.present_preference, Some(&self.swapchain)) {
+            Ok(r) => r,
+            // The window size changed again while recreating, try again on the next frame.
+            Err(VulrustError::Swapchain(SwapchainCreationError::UnsupportedDimensions)) => return Ok(()),
+            Err(err) => return Err(err)
+        };
+
+        self.framebuffers = build_framebuffers(self.device.clone(), self.render_pass.clone(), &buffers)?;
+        self.dynamic_state.viewports = Some(vec![viewport(swapchain.dimensions())]);
+        self.swapchain = swapchain;
+        self.present_mode = present_mode;
+        self.recreate_swapchain = false;
+        Ok(())
+    }
+
+    pub fn draw_frame(&mut self, world: &World, drawables: &[Box<Drawable>]) -> Result<(), VulrustError> {
+        if let Some(ref mut previous_frame_end) = self.previous_frame_end {
+            previous_frame_end.cleanup_finished();
+        }
+
+        if self.recreate_swapchain {
+            self.recreate_swapchain()?;
+            if self.recreate_swapchain {
+                return Ok(());
+            }
+        }
+
+        // Blocks only while the GPU still works on the frame that last used this slot.
+        let frame = self.frames.begin_frame()?;
+
+        let (index, acq_future) = match vulkano::swapchain::acquire_next_image(self.swapchain.clone(), None) {
+            Ok(r) => r,
+            Err(AcquireError::OutOfDate) => {
+                self.recreate_swapchain = true;
+                return Ok(());
+            },
+            Err(err) => return Err(err.into())
+        };
+
+        let uniforms = FrameUniforms::new(&self.frames.current().world_uniforms, frame, world)?;
+
+        let mut command_buffer_builder = AutoCommandBufferBuilder::primary_one_time_submit(self.device.clone(), self.queues.graphics.family())?
+            .begin_render_pass(self.framebuffers[index].clone(), false, vec![self.clear_color.into(), 1f32.into()])?;
+
+        command_buffer_builder = drawable::draw_all(command_buffer_builder, &self.dynamic_state, &uniforms, drawables)?;
+
+        let command_buffer = command_buffer_builder
+            .end_render_pass()?
+
+            .build()?;
+
+        let future = self.take_previous_frame_end().join(acq_future)
+            .then_execute(self.queues.graphics.clone(), command_buffer)?
+            .then_swapchain_present(self.queues.present.clone(), self.swapchain.clone(), index);
+        let future = (Box::new(future) as Box<GpuFuture + Send + Sync>).then_signal_fence_and_flush();
+
+        self.previous_frame_end = match future {
+            Ok(future) => {
+                // vulkano only lets a fence future be waited on and chained at once through
+                // an `Arc`.
+                let fence = Arc::new(future);
+                self.frames.end_frame(Some(fence.clone()));
+                Some(Box::new(fence) as Box<_>)
+            },
+            Err(FlushError::OutOfDate) => {
+                self.recreate_swapchain = true;
+                Some(Box::new(vulkano::sync::now(self.device.clone())) as Box<_>)
+            },
+            Err(err) => return Err(err.into())
+        };
+        Ok(())
+    }
+}
+
+fn viewport(dimensions: [u32; 2]) -> Viewport {
+    Viewport {
+        origin: [0.0, 0.0],
+        dimensions: [dimensions[0] as f32, dimensions[1] as f32],
+        depth_range: 0.0 .. 1.0,
+    }
+}
+
+type SwapchainParts = (Arc<Swapchain<Window>>, Vec<Arc<SwapchainImage<Window>>>, PresentMode);
+
+// Creates the swapchain for `surface`, taking over from `old_swapchain` if there is one.
+// The present mode is picked from what the surface supports right now.
+fn build_swapchain(
+    device: Arc<Device>,
+    surface: Arc<Surface<Window>>,
+    queues: &Queues,
+    format: Format,
+    dimensions: [u32; 2],
+    preference: PresentPreference,
+    old_swapchain: Option<&Arc<Swapchain<Window>>>
+) -> Result<SwapchainParts, VulrustError> {
+    let caps = surface.capabilities(device.physical_device())?;
+    let present_mode = preference.choose(&caps.present_modes);
+    println!("PresentMode: {:?} (asked for {})", present_mode, preference);
+    // Mailbox needs an image to spare to replace waiting frames without blocking.
+    let buffers_count = match (present_mode, caps.max_image_count) {
+        (PresentMode::Mailbox, Some(max)) => (caps.min_image_count + 1).min(max),
+        (PresentMode::Mailbox, None) => caps.min_image_count + 1,
+        _ => caps.min_image_count
+    };
+    let usage = ImageUsage {
+        color_attachment: true,
+        .. ImageUsage::none()
+    };
+
+    let (swapchain, buffers) = Swapchain::new(
+        // Create the swapchain in this `device`'s memory.
+        device,
+        // The surface where the images will be presented.
+        surface,
+        // How many buffers to use in the swapchain.
+        buffers_count,
+        // The format of the images.
+        format,
+        // The size of each image.
+        dimensions,
+        // How many layers each image has.
+        1,
+        // What the images are going to be used for.
+        usage,
+        // Describes which queues will interact with the swapchain.
+        queues.swapchain_sharing(),
+        // What transformation to use with the surface.
+        vulkano::swapchain::SurfaceTransform::Identity,
+        // How to handle the alpha channel.
+        vulkano::swapchain::CompositeAlpha::Opaque,
+        // How to present images.
+        present_mode,
+        // Clip the parts of the buffer which aren't visible.
+        true,
+        // The swapchain this one replaces, if any.
+        old_swapchain
+    )?;
+    Ok((swapchain, buffers, present_mode))
+}
+
+fn build_framebuffers(
+    device: Arc<Device>,
+    render_pass: Arc<RenderPassAbstract + Send + Sync>,
+    buffers: &[Arc<SwapchainImage<Window>>]
+) -> Result<Vec<Arc<FramebufferAbstract + Send + Sync>>, VulrustError> {
+    let depth_buffer = AttachmentImage::transient(device.clone(), buffers[0].dimensions(), Format::D16Unorm)?;
+    buffers.iter().map(|buffer| {
+        let framebuffer = Framebuffer::start(render_pass.clone())
+            .add(buffer.clone())?
+            .add(depth_buffer.clone())?
+            .build()?;
+        Ok(Arc::new(framebuffer) as Arc<FramebufferAbstract + Send + Sync>)
+    }).collect()
+}
@@ -0,0 +1,195 @@
+use std::sync::Arc;
+use vulkano::device::Device;
+use vulkano::framebuffer::RenderPassAbstract;
+use vulkano::sync::GpuFuture;
+use rand::Rng;
+
+use drawable::Drawable;
+use error::VulrustError;
+use gfx_object::GfxObject;
+use gfx_object::GfxObject3D;
+use gfx_object::GfxObjectHMap;
+use gfx_object::GfxObjectMesh;
+use gltf_loader::GltfScene;
+use rectangle_instance::RectangleInstance;
+use rectangle_instance_builder::RectangleInstanceBuilder;
+use shader_reload::{ShaderError, ShaderLibrary};
+use terrain;
+use terrain::Heightfield;
+use terrain::TerrainDesc;
+use upload::Uploads;
+
+
+// Which parts of the scene get built, so fixed scenes can be rendered one object at a time.
+#[derive(Debug, Clone, Copy)]
+pub struct SceneParts {
+    pub terrain: bool,
+    pub cube: bool,
+    pub rectangles: bool
+}
+
+impl SceneParts {
+    pub fn all() -> SceneParts {
+        SceneParts { terrain: true, cube: true, rectangles: true }
+    }
+
+    pub fn from_name(name: &str) -> Option<SceneParts> {
+        let none = SceneParts { terrain: false, cube: false, rectangles: false };
+        match name {
+            "all" => Some(SceneParts::all()),
+            "terrain" => Some(SceneParts { terrain: true, .. none }),
+            "cube" => Some(SceneParts { cube: true, .. none }),
+            "rectangles" => Some(SceneParts { rectangles: true, .. none }),
+            _ => None
+        }
+    }
+}
+
+pub struct Scene {
+    pub drawables: Vec<Box<Drawable>>,
+    // CPU copy of the terrain surface, present when the terrain was built.
+    pub heightfield: Option<Heightfield>
+}
+
+impl Scene {
+    // Builds the demo scene: the instanced rectangles, the cube and the heightmap terrain described by `terrain`.
+    // The returned future uploads the geometry and the heightmap texture on the transfer queue and has to be joined before the first draw.
+    // Rectangle instance positions and colours come from `rng`, pass a seeded one for reproducible frames.
+    pub fn new<R: Rng>(device: Arc<Device>, uploads: &Uploads, render_pass: Arc<RenderPassAbstract + Send + Sync>, parts: SceneParts, terrain: &TerrainDesc, rng: &mut R) -> Result<(Scene, Box<GpuFuture + Send + Sync>), VulrustError> {
+        let mut drawables: Vec<Box<Drawable>> = Vec::new();
+        let mut heightfield = None;
+        let mut future = Box::new(vulkano::sync::now(device.clone())) as Box<GpuFuture + Send + Sync>;
+
+        if parts.rectangles {
+            let mut rectangle = GfxObject::new(device.clone(), uploads.clone(), render_pass.clone())?;
+            let rectangle_future = rectangle.create_rectangle(1.0, 1.0)?;
+            future = Box::new(future.join(rectangle_future)) as Box<GpuFuture + Send + Sync>;
+
+            let mut rectangle_instances: Vec<RectangleInstance> = Vec::new();
+            for _i in 0..100 {
+                rectangle_instances.push(RectangleInstanceBuilder::create(
+                    [
+                        -5.0 + rng.gen::<f32>() * 10.0,
+                        -5.0 + rng.gen::<f32>() * 10.0,
+                        10.0 - rng.gen::<f32>() * 20.0
+                    ],
+                    [
+                        rng.gen::<f32>(),
+                        rng.gen::<f32>(),
+                        rng.gen::<f32>()
+                    ]
+                ));
+            }
+            let instances_future = rectangle.set_instances(&rectangle_instances)?;
+            future = Box::new(future.join(instances_future)) as Box<GpuFuture + Send + Sync>;
+
+            drawables.push(Box::new(rectangle));
+        }
+
+        if parts.cube {
+            let mut cube = GfxObject3D::new(device.clone(), uploads.clone(), render_pass.clone())?;
+            let cube_future = cube.create_cube()?;
+            future = Box::new(future.join(cube_future)) as Box<GpuFuture + Send + Sync>;
+
+            drawables.push(Box::new(cube));
+        }
+
+        if parts.terrain {
+            let mut terrain_plane = GfxObjectHMap::new(device.clone(), uploads.clone(), render_pass.clone())?;
+            let terrain_future = terrain_plane.create_plane_square(terrain.resolution, terrain.cell_size)?;
+            future = Box::new(future.join(terrain_future)) as Box<GpuFuture + Send + Sync>;
+            terrain_plane.height_scale = terrain.height_scale;
+            terrain_plane.offset = terrain.offset;
+
+            let (image_sample, image_sample_future) = {
+                let heightmap = terrain::load_heightmap(&terrain.heightmap)?;
+
+                let uploaded = uploads.image(
+                    heightmap.samples.iter().cloned(),
+                    vulkano::image::Dimensions::Dim2d { width: heightmap.width, height: heightmap.height },
+                    vulkano::format::R16Unorm
+                )?;
+                heightfield = Some(Heightfield::new(heightmap, terrain));
+                uploaded
+            };
+
+            let sampler = vulkano::sampler::Sampler::new(
+                    device.clone(),
+                    vulkano::sampler::Filter::Linear,
+                    vulkano::sampler::Filter::Linear,
+                    vulkano::sampler::MipmapMode::Nearest,
+                    vulkano::sampler::SamplerAddressMode::Repeat,
+                    vulkano::sampler::SamplerAddressMode::Repeat,
+                    vulkano::sampler::SamplerAddressMode::Repeat,
+                    0.0, 1.0, 0.0, 0.0
+                )?;
+
+            terrain_plane.set_height_map(image_sample, sampler)?;
+            future = Box::new(future.join(image_sample_future)) as Box<GpuFuture + Send + Sync>;
+
+            drawables.push(Box::new(terrain_plane));
+        }
+
+        Ok((Scene { drawables: drawables, heightfield: heightfield }, future))
+    }
+
+    // Adds every primitive of a loaded glTF scene as a drawable.
+    // The returned future uploads the geometry and textures and has to be joined before the next draw.
+    pub fn add_gltf(&mut self, device: Arc<Device>, uploads: &Uploads, render_pass: Arc<RenderPassAbstract + Send + Sync>, gltf: &GltfScene) -> Result<Box<GpuFuture + Send + Sync>, VulrustError> {
+        let mut future = Box::new(vulkano::sync::now(device.clone())) as Box<GpuFuture + Send + Sync>;
+
+        let mut textures = Vec::new();
+        for texture in gltf.textures.iter() {
+            let (image, image_future) = uploads.image(
+                texture.pixels.iter().cloned(),
+                vulkano::image::Dimensions::Dim2d { width: texture.width, height: texture.height },
+                vulkano::format::R8G8B8A8Srgb
+            )?;
+            textures.push(image);
+            future = Box::new(future.join(image_future)) as Box<GpuFuture + Send + Sync>;
+        }
+
+        // Primitives without a base colour texture sample this one, so only the factor is left.
+        let (white, white_future) = uploads.image(
+            vec![255u8, 255, 255, 255].into_iter(),
+            vulkano::image::Dimensions::Dim2d { width: 1, height: 1 },
+            vulkano::format::R8G8B8A8Srgb
+        )?;
+        future = Box::new(future.join(white_future)) as Box<GpuFuture + Send + Sync>;
+
+        let sampler = vulkano::sampler::Sampler::new(
+                device.clone(),
+                vulkano::sampler::Filter::Linear,
+                vulkano::sampler::Filter::Linear,
+                vulkano::sampler::MipmapMode::Nearest,
+                vulkano::sampler::SamplerAddressMode::Repeat,
+                vulkano::sampler::SamplerAddressMode::Repeat,
+                vulkano::sampler::SamplerAddressMode::Repeat,
+                0.0, 1.0, 0.0, 0.0
+            )?;
+
+        for primitive in gltf.primitives.iter().filter(|primitive| !primitive.geometry.indices.is_empty()) {
+            let mut mesh = GfxObjectMesh::new(device.clone(), uploads.clone(), render_pass.clone())?;
+            let mesh_future = mesh.create_from_mesh(primitive.geometry.clone())?;
+            future = Box::new(future.join(mesh_future)) as Box<GpuFuture + Send + Sync>;
+            mesh.transform = primitive.transform;
+            mesh.base_color = primitive.base_color;
+
+            let texture = match primitive.base_color_texture {
+                Some(index) => textures[index].clone(),
+                None => white.clone()
+            };
+            mesh.set_base_color_texture(texture, sampler.clone())?;
+
+            self.drawables.push(Box::new(mesh));
+        }
+
+        Ok(future)
+    }
+
+    // Rebuilds every pipeline that has shaders in `library`. Drawables that fail keep their
+    // old pipeline, the errors are returned for logging.
+    pub fn reload_shaders(&mut self, library: &ShaderLibrary) -> Vec<ShaderError> {
+        self.drawables.iter_mut().filter_map(|drawable| drawable.reload_shaders(library).err()).collect()
+    }
+}
@@ -0,0 +1,421 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use vulkano::descriptor::descriptor::{DescriptorType, ShaderStages};
+use vulkano::descriptor::pipeline_layout::PipelineLayoutDesc;
+use vulkano::format::Format;
+use vulkano::pipeline::shader::ShaderInterfaceDef;
+use vulkano::pipeline::vertex::VertexMemberInfo;
+
+use shader_reload::{fragment_stage, vertex_stage, ShaderStage};
+
+
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+
+// The handful of SPIR-V opcodes, decorations and enums the interface is made of.
+const OP_NAME: u32 = 5;
+const OP_ENTRY_POINT: u32 = 15;
+const OP_TYPE_INT: u32 = 21;
+const OP_TYPE_FLOAT: u32 = 22;
+const OP_TYPE_VECTOR: u32 = 23;
+const OP_TYPE_MATRIX: u32 = 24;
+const OP_TYPE_IMAGE: u32 = 25;
+const OP_TYPE_SAMPLER: u32 = 26;
+const OP_TYPE_SAMPLED_IMAGE: u32 = 27;
+const OP_TYPE_ARRAY: u32 = 28;
+const OP_TYPE_RUNTIME_ARRAY: u32 = 29;
+const OP_TYPE_STRUCT: u32 = 30;
+const OP_TYPE_POINTER: u32 = 32;
+const OP_CONSTANT: u32 = 43;
+const OP_VARIABLE: u32 = 59;
+const OP_DECORATE: u32 = 71;
+const OP_MEMBER_DECORATE: u32 = 72;
+
+const DECORATION_BLOCK: u32 = 2;
+const DECORATION_BUFFER_BLOCK: u32 = 3;
+const DECORATION_ARRAY_STRIDE: u32 = 6;
+const DECORATION_MATRIX_STRIDE: u32 = 7;
+const DECORATION_BUILT_IN: u32 = 11;
+const DECORATION_LOCATION: u32 = 30;
+const DECORATION_BINDING: u32 = 33;
+const DECORATION_DESCRIPTOR_SET: u32 = 34;
+const DECORATION_OFFSET: u32 = 35;
+
+const STORAGE_UNIFORM_CONSTANT: u32 = 0;
+const STORAGE_INPUT: u32 = 1;
+const STORAGE_UNIFORM: u32 = 2;
+const STORAGE_OUTPUT: u32 = 3;
+const STORAGE_PUSH_CONSTANT: u32 = 9;
+const STORAGE_STORAGE_BUFFER: u32 = 12;
+
+const EXECUTION_MODEL_VERTEX: u32 = 0;
+const EXECUTION_MODEL_FRAGMENT: u32 = 4;
+const DIM_SUBPASS_DATA: u32 = 6;
+
+// What a SPIR-V module asks of the pipeline it runs in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShaderInterface {
+    pub inputs: Vec<InterfaceVariable>,
+    pub outputs: Vec<InterfaceVariable>,
+    pub descriptors: Vec<DescriptorBinding>,
+    // Bytes of push constants used, zero without a push constant block.
+    pub push_constants_size: u32
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceVariable {
+    pub name: Option<String>,
+    pub location: u32,
+    // Format of each location, matrices and arrays take up more than one.
+    pub format: Format,
+    pub locations: u32
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DescriptorBinding {
+    pub name: Option<String>,
+    pub set: u32,
+    pub binding: u32,
+    pub ty: DescriptorType
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterfaceError {
+    // Not SPIR-V, or something the reflection doesn't understand.
+    Malformed(String),
+    MissingEntryPoint(ShaderStage),
+    // An input at a location the pipeline feeds nothing into.
+    UnexpectedInput { name: Option<String>, location: u32 },
+    // An output the next stage reads is missing.
+    MissingOutput { name: Option<String>, location: u32 },
+    Format { location: u32, expected: Format, found: Format },
+    // The pipeline feeds the location from a vertex field that doesn't exist or doesn't fit.
+    MissingVertexMember(String),
+    VertexMemberFormat { name: String, found: Format },
+    UnknownDescriptor { set: u32, binding: u32 },
+    DescriptorType { set: u32, binding: u32, expected: Option<DescriptorType>, found: DescriptorType },
+    PushConstants { size: u32, available: u32 }
+}
+
+impl fmt::Display for InterfaceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            InterfaceError::Malformed(ref reason) => write!(f, "malformed SPIR-V: {}", reason),
+            InterfaceError::MissingEntryPoint(stage) => write!(f, "no {:?} entry point called main", stage),
+            InterfaceError::UnexpectedInput { ref name, location } => write!(f, "input {:?} at location {} isn't provided", name, location),
+            InterfaceError::MissingOutput { ref name, location } => write!(f, "output {:?} at location {} is missing", name, location),
+            InterfaceError::Format { location, expected, found } => write!(f, "location {} is {:?}, expected {:?}", location, found, expected),
+            InterfaceError::MissingVertexMember(ref name) => write!(f, "no vertex field called {}", name),
+            InterfaceError::VertexMemberFormat { ref name, found } => write!(f, "vertex field {} can't be read as {:?}", name, found),
+            InterfaceError::UnknownDescriptor { set, binding } => write!(f, "no descriptor at set {}, binding {}", set, binding),
+            InterfaceError::DescriptorType { set, binding, expected, found } => write!(f, "descriptor at set {}, binding {} is {:?}, expected {:?}", set, binding, found, expected),
+            InterfaceError::PushConstants { size, available } => write!(f, "uses {} bytes of push constants, only {} available", size, available)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Type {
+    Scalar { float: bool, signed: bool, width: u32 },
+    Vector(u32, u32),
+    Matrix(u32, u32),
+    Array(u32, u32),
+    RuntimeArray(u32),
+    Struct(Vec<u32>),
+    Image { dim: u32, sampled: u32 },
+    Sampler,
+    SampledImage,
+    Pointer(u32)
+}
+
+// Everything `reflect` collects in one pass over the module.
+#[derive(Default)]
+struct Module {
+    entry_points: Vec<(u32, String, Vec<u32>)>,
+    names: HashMap<u32, String>,
+    types: HashMap<u32, Type>,
+    constants: HashMap<u32, u32>,
+    variables: Vec<(u32, u32, u32)>,
+    decorations: HashMap<u32, Vec<(u32, u32)>>,
+    member_decorations: HashMap<(u32, u32), Vec<(u32, u32)>>
+}
+
+impl Module {
+    fn parse(spirv: &[u32]) -> Result<Module, InterfaceError> {
+        if spirv.len() < 5 || spirv[0] != SPIRV_MAGIC {
+            return Err(InterfaceError::Malformed("missing SPIR-V header".to_string()));
+        }
+        let mut module = Module::default();
+        let mut i = 5;
+        while i < spirv.len() {
+            let opcode = spirv[i] & 0xffff;
+            let count = (spirv[i] >> 16) as usize;
+            if count == 0 || i + count > spirv.len() {
+                return Err(InterfaceError::Malformed(format!("truncated instruction at word {}", i)));
+            }
+            let operands = &spirv[i + 1 .. i + count];
+            // Reads operand `n`, instructions that are too short for their opcode are malformed.
+            let op = |n: usize| operands.get(n).cloned().ok_or_else(|| InterfaceError::Malformed(format!("short instruction at word {}", i)));
+            match opcode {
+                OP_NAME => { module.names.insert(op(0)?, string(&operands[1 ..])); },
+                OP_ENTRY_POINT => {
+                    let name = string(operands.get(2 ..).unwrap_or(&[]));
+                    let interface_start = 2 + (name.len() + 4) / 4;
+                    let interface = operands.get(interface_start ..).unwrap_or(&[]).to_vec();
+                    module.entry_points.push((op(0)?, name, interface));
+                },
+                OP_TYPE_INT => { module.types.insert(op(0)?, Type::Scalar { float: false, signed: op(2)? == 1, width: op(1)? }); },
+                OP_TYPE_FLOAT => { module.types.insert(op(0)?, Type::Scalar { float: true, signed: true, width: op(1)? }); },
+                OP_TYPE_VECTOR => { module.types.insert(op(0)?, Type::Vector(op(1)?, op(2)?)); },
+                OP_TYPE_MATRIX => { module.types.insert(op(0)?, Type::Matrix(op(1)?, op(2)?)); },
+                OP_TYPE_IMAGE => { module.types.insert(op(0)?, Type::Image { dim: op(2)?, sampled: op(6)? }); },
+                OP_TYPE_SAMPLER => { module.types.insert(op(0)?, Type::Sampler); },
+                OP_TYPE_SAMPLED_IMAGE => { module.types.insert(op(0)?, Type::SampledImage); },
+                OP_TYPE_ARRAY => { module.types.insert(op(0)?, Type::Array(op(1)?, op(2)?)); },
+                OP_TYPE_RUNTIME_ARRAY => { module.types.insert(op(0)?, Type::RuntimeArray(op(1)?)); },
+                OP_TYPE_STRUCT => { module.types.insert(op(0)?, Type::Struct(operands[1 ..].to_vec())); },
+                OP_TYPE_POINTER => { module.types.insert(op(0)?, Type::Pointer(op(2)?)); },
+                OP_CONSTANT => { module.constants.insert(op(1)?, op(2)?); },
+                OP_VARIABLE => module.variables.push((op(1)?, op(0)?, op(2)?)),
+                OP_DECORATE => module.decorations.entry(op(0)?).or_insert_with(Vec::new).push((op(1)?, operands.get(2).cloned().unwrap_or(0))),
+                OP_MEMBER_DECORATE => module.member_decorations.entry((op(0)?, op(1)?)).or_insert_with(Vec::new).push((op(2)?, operands.get(3).cloned().unwrap_or(0))),
+                _ => ()
+            }
+            i += count;
+        }
+        Ok(module)
+    }
+
+    fn decoration(&self, id: u32, decoration: u32) -> Option<u32> {
+        self.decorations.get(&id).and_then(|decorations| decorations.iter().find(|&&(d, _)| d == decoration)).map(|&(_, value)| value)
+    }
+
+    fn member_decoration(&self, id: u32, member: u32, decoration: u32) -> Option<u32> {
+        self.member_decorations.get(&(id, member)).and_then(|decorations| decorations.iter().find(|&&(d, _)| d == decoration)).map(|&(_, value)| value)
+    }
+
+    fn ty(&self, id: u32) -> Result<&Type, InterfaceError> {
+        self.types.get(&id).ok_or_else(|| InterfaceError::Malformed(format!("unknown type %{}", id)))
+    }
+
+    fn array_length(&self, length_id: u32) -> Result<u32, InterfaceError> {
+        self.constants.get(&length_id).cloned().ok_or_else(|| InterfaceError::Malformed(format!("unknown array length %{}", length_id)))
+    }
+
+    // Builtins like `gl_Position` come as decorated variables or as blocks of decorated members.
+    fn is_built_in(&self, variable: u32, pointee: u32) -> bool {
+        self.decoration(variable, DECORATION_BUILT_IN).is_some() || self.member_decoration(pointee, 0, DECORATION_BUILT_IN).is_some()
+    }
+
+    // Format of one location and the number of locations taken.
+    fn location_format(&self, id: u32) -> Result<(Format, u32), InterfaceError> {
+        match *self.ty(id)? {
+            Type::Scalar { .. } => Ok((self.format(id, 1)?, 1)),
+            Type::Vector(component, count) => Ok((self.format(component, count)?, 1)),
+            Type::Matrix(column, count) => Ok((self.location_format(column)?.0, count)),
+            Type::Array(element, length) => {
+                let (format, locations) = self.location_format(element)?;
+                Ok((format, locations * self.array_length(length)?))
+            },
+            ref other => Err(InterfaceError::Malformed(format!("{:?} can't be a shader input or output", other)))
+        }
+    }
+
+    fn format(&self, scalar: u32, count: u32) -> Result<Format, InterfaceError> {
+        let formats = match *self.ty(scalar)? {
+            Type::Scalar { float: true, width: 32, .. } => [Format::R32Sfloat, Format::R32G32Sfloat, Format::R32G32B32Sfloat, Format::R32G32B32A32Sfloat],
+            Type::Scalar { float: true, width: 64, .. } => [Format::R64Sfloat, Format::R64G64Sfloat, Format::R64G64B64Sfloat, Format::R64G64B64A64Sfloat],
+            Type::Scalar { float: false, signed: true, width: 32 } => [Format::R32Sint, Format::R32G32Sint, Format::R32G32B32Sint, Format::R32G32B32A32Sint],
+            Type::Scalar { float: false, signed: false, width: 32 } => [Format::R32Uint, Format::R32G32Uint, Format::R32G32B32Uint, Format::R32G32B32A32Uint],
+            ref other => return Err(InterfaceError::Malformed(format!("unsupported component type {:?}", other)))
+        };
+        (count as usize).checked_sub(1).and_then(|i| formats.get(i)).cloned().ok_or_else(|| InterfaceError::Malformed(format!("vector of {} components", count)))
+    }
+
+    fn descriptor_type(&self, storage: u32, pointee: u32) -> Result<DescriptorType, InterfaceError> {
+        let id = match *self.ty(pointee)? {
+            Type::Array(element, _) | Type::RuntimeArray(element) => element,
+            _ => pointee
+        };
+        match (storage, self.ty(id)?) {
+            (STORAGE_UNIFORM, _) if self.decoration(id, DECORATION_BUFFER_BLOCK).is_some() => Ok(DescriptorType::StorageBuffer),
+            (STORAGE_UNIFORM, _) if self.decoration(id, DECORATION_BLOCK).is_some() => Ok(DescriptorType::UniformBuffer),
+            (STORAGE_STORAGE_BUFFER, _) => Ok(DescriptorType::StorageBuffer),
+            (STORAGE_UNIFORM_CONSTANT, &Type::SampledImage) => Ok(DescriptorType::CombinedImageSampler),
+            (STORAGE_UNIFORM_CONSTANT, &Type::Sampler) => Ok(DescriptorType::Sampler),
+            (STORAGE_UNIFORM_CONSTANT, &Type::Image { dim: DIM_SUBPASS_DATA, .. }) => Ok(DescriptorType::InputAttachment),
+            (STORAGE_UNIFORM_CONSTANT, &Type::Image { sampled: 2, .. }) => Ok(DescriptorType::StorageImage),
+            (STORAGE_UNIFORM_CONSTANT, &Type::Image { .. }) => Ok(DescriptorType::SampledImage),
+            (_, other) => Err(InterfaceError::Malformed(format!("unsupported descriptor type {:?}", other)))
+        }
+    }
+
+    // Size in bytes, as laid out by the offset and stride decorations.
+    fn size(&self, id: u32) -> Result<u32, InterfaceError> {
+        match *self.ty(id)? {
+            Type::Scalar { width, .. } => Ok(width / 8),
+            Type::Vector(component, count) => Ok(self.size(component)? * count),
+            Type::Matrix(column, count) => Ok(self.size(column)? * count),
+            Type::Array(element, length) => {
+                let stride = match self.decoration(id, DECORATION_ARRAY_STRIDE) {
+                    Some(stride) => stride,
+                    None => self.size(element)?
+                };
+                Ok(stride * self.array_length(length)?)
+            },
+            Type::Struct(ref members) => {
+                let mut size = 0;
+                for (member, &member_type) in members.iter().enumerate() {
+                    let member = member as u32;
+                    let offset = self.member_decoration(id, member, DECORATION_OFFSET).unwrap_or(size);
+                    let member_size = match (self.ty(member_type)?, self.member_decoration(id, member, DECORATION_MATRIX_STRIDE)) {
+                        (&Type::Matrix(_, columns), Some(stride)) => stride * columns,
+                        _ => self.size(member_type)?
+                    };
+                    size = size.max(offset + member_size);
+                }
+                Ok(size)
+            },
+            ref other => Err(InterfaceError::Malformed(format!("{:?} has no size", other)))
+        }
+    }
+}
+
+// Null-terminated string packed into words.
+fn string(words: &[u32]) -> String {
+    let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes().to_vec()).take_while(|&byte| byte != 0).collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+// Reads the interface of the `main` entry point of `stage` out of a SPIR-V module.
+pub fn reflect(spirv: &[u32], stage: ShaderStage) -> Result<ShaderInterface, InterfaceError> {
+    let module = Module::parse(spirv)?;
+    let execution_model = match stage {
+        ShaderStage::Vertex => EXECUTION_MODEL_VERTEX,
+        ShaderStage::Fragment => EXECUTION_MODEL_FRAGMENT
+    };
+    let entry_interface = module.entry_points.iter()
+        .find(|&&(model, ref name, _)| model == execution_model && name == "main")
+        .map(|(_, _, interface)| interface)
+        .ok_or(InterfaceError::MissingEntryPoint(stage))?;
+
+    let mut interface = ShaderInterface { inputs: Vec::new(), outputs: Vec::new(), descriptors: Vec::new(), push_constants_size: 0 };
+    for &(id, pointer, storage) in module.variables.iter() {
+        let pointee = match *module.ty(pointer)? {
+            Type::Pointer(pointee) => pointee,
+            _ => return Err(InterfaceError::Malformed(format!("variable %{} isn't a pointer", id)))
+        };
+        let name = module.names.get(&id).cloned();
+        match storage {
+            STORAGE_INPUT | STORAGE_OUTPUT if entry_interface.contains(&id) && !module.is_built_in(id, pointee) => {
+                let location = module.decoration(id, DECORATION_LOCATION).ok_or_else(|| InterfaceError::Malformed(format!("{:?} has no location", name)))?;
+                let (format, locations) = module.location_format(pointee)?;
+                let variable = InterfaceVariable { name: name, location: location, format: format, locations: locations };
+                if storage == STORAGE_INPUT { interface.inputs.push(variable) } else { interface.outputs.push(variable) }
+            },
+            STORAGE_UNIFORM | STORAGE_UNIFORM_CONSTANT | STORAGE_STORAGE_BUFFER => {
+                interface.descriptors.push(DescriptorBinding {
+                    ty: module.descriptor_type(storage, pointee)?,
+                    name: name,
+                    set: module.decoration(id, DECORATION_DESCRIPTOR_SET).unwrap_or(0),
+                    binding: module.decoration(id, DECORATION_BINDING).unwrap_or(0)
+                });
+            },
+            STORAGE_PUSH_CONSTANT => interface.push_constants_size = interface.push_constants_size.max(module.size(pointee)?),
+            _ => ()
+        }
+    }
+    interface.inputs.sort_by_key(|variable| variable.location);
+    interface.outputs.sort_by_key(|variable| variable.location);
+    Ok(interface)
+}
+
+impl ShaderInterface {
+    // Every input has to come from the pipeline with the same format. Outputs may be added,
+    // but every output the pipeline expects has to stay.
+    pub fn check_variables<I, O>(&self, inputs: &I, outputs: &O) -> Result<(), InterfaceError>
+        where I: ShaderInterfaceDef, O: ShaderInterfaceDef
+    {
+        let inputs: Vec<_> = inputs.elements().collect();
+        for input in self.inputs.iter() {
+            let expected = inputs.iter().find(|expected| expected.location.start == input.location)
+                .ok_or_else(|| InterfaceError::UnexpectedInput { name: input.name.clone(), location: input.location })?;
+            if expected.format != input.format {
+                return Err(InterfaceError::Format { location: input.location, expected: expected.format, found: input.format });
+            }
+        }
+        for expected in outputs.elements() {
+            let output = self.outputs.iter().find(|output| output.location == expected.location.start)
+                .ok_or_else(|| InterfaceError::MissingOutput { name: expected.name.as_ref().map(|name| name.to_string()), location: expected.location.start })?;
+            if expected.format != output.format {
+                return Err(InterfaceError::Format { location: output.location, expected: expected.format, found: output.format });
+            }
+        }
+        Ok(())
+    }
+
+    // The vertex fields the pipeline reads each input from, looked up by the names `inputs`
+    // gives the locations, have to fit what the shader reads there.
+    pub fn check_vertex_members<I, M>(&self, inputs: &I, member: M) -> Result<(), InterfaceError>
+        where I: ShaderInterfaceDef, M: Fn(&str) -> Option<VertexMemberInfo>
+    {
+        for expected in inputs.elements() {
+            let name = match expected.name {
+                Some(ref name) => name.to_string(),
+                None => continue
+            };
+            let input = match self.inputs.iter().find(|input| input.location == expected.location.start) {
+                Some(input) => input,
+                None => continue
+            };
+            let info = member(&name).ok_or_else(|| InterfaceError::MissingVertexMember(name.clone()))?;
+            if !info.ty.matches(info.array_size, input.format, input.locations) {
+                return Err(InterfaceError::VertexMemberFormat { name: name, found: input.format });
+            }
+        }
+        Ok(())
+    }
+
+    // Every descriptor has to be in the layout with the same type, push constants have to fit
+    // into the ranges of `stages`.
+    pub fn check_layout<L: PipelineLayoutDesc>(&self, layout: &L, stages: ShaderStages) -> Result<(), InterfaceError> {
+        for descriptor in self.descriptors.iter() {
+            let (set, binding) = (descriptor.set, descriptor.binding);
+            let expected = layout.descriptor(set as usize, binding as usize).ok_or(InterfaceError::UnknownDescriptor { set: set, binding: binding })?;
+            let expected = expected.ty.ty();
+            if expected != Some(descriptor.ty) {
+                return Err(InterfaceError::DescriptorType { set: set, binding: binding, expected: expected, found: descriptor.ty });
+            }
+        }
+
+        let available = (0 .. layout.num_push_constants_ranges())
+            .filter_map(|range| layout.push_constants_range(range))
+            .filter(|range| range.stages.intersects(&stages))
+            .map(|range| (range.offset + range.size) as u32)
+            .max()
+            .unwrap_or(0);
+        if self.push_constants_size > available {
+            return Err(InterfaceError::PushConstants { size: self.push_constants_size, available: available });
+        }
+        Ok(())
+    }
+}
+
+// Checks a vertex shader against the `shader!` types of the compiled-in shader it replaces
+// and the vertex fields the pipeline feeds it from.
+pub fn check_vertex_shader<I, O, L, M>(spirv: &[u32], inputs: &I, outputs: &O, layout: &L, member: M) -> Result<(), InterfaceError>
+    where I: ShaderInterfaceDef, O: ShaderInterfaceDef, L: PipelineLayoutDesc, M: Fn(&str) -> Option<VertexMemberInfo>
+{
+    let interface = reflect(spirv, ShaderStage::Vertex)?;
+    interface.check_variables(inputs, outputs)?;
+    interface.check_vertex_members(inputs, member)?;
+    interface.check_layout(layout, vertex_stage())
+}
+
+pub fn check_fragment_shader<I, O, L>(spirv: &[u32], inputs: &I, outputs: &O, layout: &L) -> Result<(), InterfaceError>
+    where I: ShaderInterfaceDef, O: ShaderInterfaceDef, L: PipelineLayoutDesc
+{
+    let interface = reflect(spirv, ShaderStage::Fragment)?;
+    interface.check_variables(inputs, outputs)?;
+    interface.check_layout(layout, fragment_stage())
+}
@@ -0,0 +1,227 @@
+use std::ffi::CStr;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use shaderc;
+use vulkano::OomError;
+use vulkano::descriptor::descriptor::ShaderStages;
+use vulkano::device::Device;
+use vulkano::pipeline::GraphicsPipelineCreationError;
+use vulkano::pipeline::shader::{GraphicsEntryPoint, GraphicsShaderType, ShaderModule};
+
+use shader_interface::InterfaceError;
+
+
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+
+// Vertex and fragment shader of one pipeline.
+type ShaderPair = (Arc<ShaderModule>, Arc<ShaderModule>);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment
+}
+
+#[derive(Debug)]
+pub enum ShaderError {
+    Io(PathBuf, io::Error),
+    Compile(PathBuf, String),
+    InvalidSpirv(PathBuf),
+    // Neither a `.vert` nor a `.frag` file, see `parse_file_name`.
+    UnrecognizedFileName(PathBuf),
+    // The rest name the shader pair (`cube`, `mesh`...) whose pipeline couldn't be rebuilt.
+    Interface(String, ShaderStage, InterfaceError),
+    Module(String, OomError),
+    Pipeline(String, GraphicsPipelineCreationError)
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ShaderError::Io(ref path, ref err) => write!(f, "{}: {}", path.display(), err),
+            ShaderError::Compile(ref path, ref err) => write!(f, "{}: {}", path.display(), err),
+            ShaderError::InvalidSpirv(ref path) => write!(f, "{}: not a SPIR-V module", path.display()),
+            ShaderError::UnrecognizedFileName(ref path) => write!(f, "{}: not a .vert or .frag shader", path.display()),
+            ShaderError::Interface(ref name, stage, ref err) => write!(f, "{} {:?} shader: {}", name, stage, err),
+            ShaderError::Module(ref name, ref err) => write!(f, "{}: {}", name, err),
+            ShaderError::Pipeline(ref name, ref err) => write!(f, "{}: {}", name, err)
+        }
+    }
+}
+
+// Name and stage of a shader file: `cube.vert` and `cube.frag` are GLSL, `cube.vert.spv` and
+// `cube.frag.spv` precompiled SPIR-V. The bool tells the latter apart.
+pub fn parse_file_name(path: &Path) -> Option<(String, ShaderStage, bool)> {
+    let file_name = path.file_name()?.to_str()?;
+    let spirv = file_name.ends_with(".spv");
+    let file_name = if spirv { &file_name[.. file_name.len() - 4] } else { file_name };
+    let dot = file_name.rfind('.')?;
+    let stage = match &file_name[dot + 1 ..] {
+        "vert" => ShaderStage::Vertex,
+        "frag" => ShaderStage::Fragment,
+        _ => return None
+    };
+    Some((file_name[.. dot].to_string(), stage, spirv))
+}
+
+pub fn compile_glsl(source: &str, stage: ShaderStage, path: &Path) -> Result<Vec<u32>, ShaderError> {
+    let kind = match stage {
+        ShaderStage::Vertex => shaderc::ShaderKind::Vertex,
+        ShaderStage::Fragment => shaderc::ShaderKind::Fragment
+    };
+    let mut compiler = shaderc::Compiler::new().ok_or_else(|| ShaderError::Compile(path.to_path_buf(), "couldn't create the shader compiler".to_string()))?;
+    let artifact = compiler.compile_into_spirv(source, kind, &path.to_string_lossy(), "main", None)
+        .map_err(|err| ShaderError::Compile(path.to_path_buf(), err.to_string()))?;
+    Ok(artifact.as_binary().to_vec())
+}
+
+// Reads a GLSL or SPIR-V shader file, see `parse_file_name`.
+pub fn load_shader(path: &Path) -> Result<Vec<u32>, ShaderError> {
+    let (_, stage, spirv) = parse_file_name(path).ok_or_else(|| ShaderError::UnrecognizedFileName(path.to_path_buf()))?;
+    let bytes = fs::read(path).map_err(|err| ShaderError::Io(path.to_path_buf(), err))?;
+    if !spirv {
+        let source = String::from_utf8_lossy(&bytes);
+        return compile_glsl(&source, stage, path);
+    }
+    if bytes.len() % 4 != 0 {
+        return Err(ShaderError::InvalidSpirv(path.to_path_buf()));
+    }
+    let words: Vec<u32> = bytes.chunks(4).map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]])).collect();
+    if words.first() != Some(&SPIRV_MAGIC) {
+        return Err(ShaderError::InvalidSpirv(path.to_path_buf()));
+    }
+    Ok(words)
+}
+
+struct ShaderFile {
+    path: PathBuf,
+    name: String,
+    stage: ShaderStage,
+    // Modification time and length when last read, to spot changes.
+    version: Option<(SystemTime, u64)>,
+    // Last version that compiled.
+    spirv: Option<Vec<u32>>
+}
+
+// Shader files in a directory, recompiled whenever they change. Drawables ask for their
+// shaders by name and keep the compiled-in ones for whatever the directory doesn't have.
+pub struct ShaderLibrary {
+    pub directory: PathBuf,
+    files: Vec<ShaderFile>
+}
+
+impl ShaderLibrary {
+    pub fn new<P: Into<PathBuf>>(directory: P) -> ShaderLibrary {
+        ShaderLibrary {
+            directory: directory.into(),
+            files: Vec::new()
+        }
+    }
+
+    // Reads new and changed shader files. Returns every file that changed, either compiled or
+    // with the reason it didn't; a file that fails keeps its previous SPIR-V.
+    pub fn poll(&mut self) -> Vec<Result<PathBuf, ShaderError>> {
+        let entries = match fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            Err(err) => return vec![Err(ShaderError::Io(self.directory.clone(), err))]
+        };
+
+        let mut results = Vec::new();
+        for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+            let (name, stage, _) = match parse_file_name(&path) {
+                Some(parsed) => parsed,
+                None => continue
+            };
+            let version = fs::metadata(&path).ok().and_then(|metadata| metadata.modified().ok().map(|modified| (modified, metadata.len())));
+
+            let index = match self.files.iter().position(|file| file.path == path) {
+                Some(index) => index,
+                None => {
+                    self.files.push(ShaderFile { path: path.clone(), name: name, stage: stage, version: None, spirv: None });
+                    self.files.len() - 1
+                }
+            };
+            let file = &mut self.files[index];
+            if file.version.is_some() && file.version == version {
+                continue;
+            }
+            file.version = version;
+            match load_shader(&path) {
+                Ok(spirv) => {
+                    file.spirv = Some(spirv);
+                    results.push(Ok(path));
+                },
+                Err(err) => results.push(Err(err))
+            }
+        }
+        results
+    }
+
+    pub fn spirv(&self, name: &str, stage: ShaderStage) -> Option<&[u32]> {
+        // `.spv` and GLSL files of the same shader are both read, the last one that changed wins.
+        self.files.iter()
+            .filter(|file| file.name == name && file.stage == stage && file.spirv.is_some())
+            .max_by_key(|file| file.version.map(|(modified, _)| modified))
+            .and_then(|file| file.spirv.as_deref())
+    }
+
+    // Modules for the shaders called `name`, `None` if the directory has neither stage.
+    // A missing stage is filled in with the current module. `check` gets to reject each
+    // shader's interface before a module is made from it.
+    pub fn modules<C>(&self, name: &str, device: Arc<Device>, vertex: &Arc<ShaderModule>, fragment: &Arc<ShaderModule>, check: C)
+        -> Result<Option<ShaderPair>, ShaderError>
+        where C: Fn(ShaderStage, &[u32]) -> Result<(), InterfaceError>
+    {
+        let module = |stage: ShaderStage, current: &Arc<ShaderModule>| -> Result<Arc<ShaderModule>, ShaderError> {
+            match self.spirv(name, stage) {
+                Some(spirv) => {
+                    check(stage, spirv).map_err(|err| ShaderError::Interface(name.to_string(), stage, err))?;
+                    // Checked against the interface the entry points will claim, see `vertex_entry_point`.
+                    unsafe { ShaderModule::from_words(device.clone(), spirv) }.map_err(|err| ShaderError::Module(name.to_string(), err))
+                },
+                None => Ok(current.clone())
+            }
+        };
+        if self.spirv(name, ShaderStage::Vertex).is_none() && self.spirv(name, ShaderStage::Fragment).is_none() {
+            return Ok(None);
+        }
+        Ok(Some((module(ShaderStage::Vertex, vertex)?, module(ShaderStage::Fragment, fragment)?)))
+    }
+}
+
+pub fn vertex_stage() -> ShaderStages {
+    ShaderStages { vertex: true, .. ShaderStages::none() }
+}
+
+pub fn fragment_stage() -> ShaderStages {
+    ShaderStages { fragment: true, .. ShaderStages::none() }
+}
+
+/// `main` of a loaded module, described by the `shader!` types of the compiled-in shader it
+/// replaces.
+///
+/// # Safety
+///
+/// The module has to really have that interface: a reloaded shader may change its code but
+/// has to keep its inputs, outputs and bindings. `shader_interface` checks for that.
+pub unsafe fn vertex_entry_point<I, O, L>(module: &ShaderModule, input: I, output: O, layout: L) -> GraphicsEntryPoint<'_, (), I, O, L> {
+    module.graphics_entry_point(main_name(), input, output, layout, GraphicsShaderType::Vertex)
+}
+
+/// Like `vertex_entry_point`.
+///
+/// # Safety
+///
+/// Same as for `vertex_entry_point`.
+pub unsafe fn fragment_entry_point<I, O, L>(module: &ShaderModule, input: I, output: O, layout: L) -> GraphicsEntryPoint<'_, (), I, O, L> {
+    module.graphics_entry_point(main_name(), input, output, layout, GraphicsShaderType::Fragment)
+}
+
+fn main_name() -> &'static CStr {
+    CStr::from_bytes_with_nul(b"main\0").unwrap()
+}
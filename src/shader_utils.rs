@@ -12,9 +12,7 @@ vulkano_shaders::shader!{
         layout(location = 0) out vec3 color;
 
         layout(set = 0, binding = 0) uniform UniformMatrices {
-            mat4 projection;
-            mat4 view;
-            mat4 model;
+            mat4 view_projection;
         } uniforms;
 
         layout(set = 1, binding = 0) uniform DeltaUniform {
@@ -56,9 +54,7 @@ vulkano_shaders::shader!{
             mat4 r_matrix;
             rotation(delta_uniform.delta, vec3(0.0, 1.0, 0.0), r_matrix);
 
-            mat4 final_world = uniforms.model * uniforms.view * uniforms.projection;
-
-            gl_Position = vec4(position, 1.0) * r_matrix * final_world;
+            gl_Position = uniforms.view_projection * (vec4(position, 1.0) * r_matrix);
         }
     "
 }
@@ -93,16 +89,13 @@ vulkano_shaders::shader!{
         layout(location = 0) out vec3 vNormal;
 
         layout(set = 0, binding = 0) uniform UniformMatrices {
-            mat4 projection;
-            mat4 view;
-            mat4 model;
+            mat4 view_projection;
         } uniforms;
 
         void main() {
             vNormal = normal;
-            mat4 final_world = uniforms.view * uniforms.projection;
 
-            gl_Position = vec4(position, 1.0) * final_world;
+            gl_Position = uniforms.view_projection * vec4(position, 1.0);
         }
     "
 }
@@ -118,15 +111,8 @@ vulkano_shaders::shader!{
 
         layout(location = 0) out vec4 f_color;
 
-        layout(set = 0, binding = 0) uniform UniformMatrices {
-            mat4 projection;
-            mat4 view;
-            mat4 model;
-        } uniforms;
-
         void main() {
             vec3 light_direction = normalize(vec3(1.0, -0.5, 0.0));
-            light_direction = (vec4(light_direction, 0.0) * uniforms.model).xyz;
 
             float k = dot(light_direction, vNormal) / (length(light_direction) * length(vNormal));
             k = max(k, 0.25);
@@ -149,21 +135,23 @@ vulkano_shaders::shader!{
         layout(location = 0) out vec2 fuv;
 
         layout(set = 0, binding = 0) uniform UniformMatrices {
-            mat4 projection;
-            mat4 view;
-            mat4 model;
+            mat4 view_projection;
         } uniforms;
         layout(set = 1, binding = 0) uniform sampler2D hmap;
 
+        layout(push_constant) uniform TerrainConstants {
+            vec3 offset;
+            float height_scale;
+        } terrain;
+
         void main() {
             fuv = uv;
 
-            mat4 final_world = uniforms.model * uniforms.view * uniforms.projection;
-
             vec3 pos = position;
-            pos.y = -(texture(hmap, fuv).x) * 5.0;
+            pos.y = -(texture(hmap, fuv).x) * terrain.height_scale;
+            pos += terrain.offset;
 
-            gl_Position = vec4(pos, 1.0) * final_world;
+            gl_Position = uniforms.view_projection * vec4(pos, 1.0);
         }
     "
 }
@@ -188,3 +176,66 @@ vulkano_shaders::shader!{
     "
 }
 }
+
+#[allow(dead_code)]
+pub mod vs_mesh {
+vulkano_shaders::shader!{
+    ty: "vertex",
+    src: "
+        #version 450
+        layout(location = 0) in vec3 position;
+        layout(location = 1) in vec3 normal;
+        layout(location = 2) in vec2 uv;
+
+        layout(location = 0) out vec3 vNormal;
+        layout(location = 1) out vec2 vUV;
+
+        layout(set = 0, binding = 0) uniform UniformMatrices {
+            mat4 view_projection;
+        } uniforms;
+
+        layout(push_constant) uniform MeshConstants {
+            mat4 node;
+            vec4 base_color;
+        } mesh;
+
+        void main() {
+            vNormal = mat3(mesh.node) * normal;
+            vUV = uv;
+
+            gl_Position = uniforms.view_projection * mesh.node * vec4(position, 1.0);
+        }
+    "
+}
+}
+
+#[allow(dead_code)]
+pub mod fs_mesh {
+vulkano_shaders::shader!{
+    ty: "fragment",
+    src: "
+        #version 450
+        layout(location = 0) in vec3 vNormal;
+        layout(location = 1) in vec2 vUV;
+
+        layout(location = 0) out vec4 f_color;
+
+        layout(set = 1, binding = 0) uniform sampler2D base_color_texture;
+
+        layout(push_constant) uniform MeshConstants {
+            mat4 node;
+            vec4 base_color;
+        } mesh;
+
+        void main() {
+            vec3 light_direction = normalize(vec3(1.0, -0.5, 0.0));
+
+            float k = dot(light_direction, vNormal) / (length(light_direction) * length(vNormal));
+            k = max(k, 0.25);
+
+            vec4 color = texture(base_color_texture, vUV) * mesh.base_color;
+            f_color = vec4(color.rgb * k, color.a);
+        }
+    "
+}
+}
@@ -0,0 +1,210 @@
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use cgmath::{InnerSpace, Point3, Vector3};
+use image;
+use png;
+use png::HasParameters;
+
+
+// Everything that shapes the terrain: which heightmap and how it is laid out in the world.
+#[derive(Debug, Clone)]
+pub struct TerrainDesc {
+    pub heightmap: PathBuf,
+    // Quads along each side of the grid, the heightmap is stretched over them.
+    pub resolution: u32,
+    pub cell_size: f32,
+    // World height of a full-white heightmap sample.
+    pub height_scale: f32,
+    // Added to every vertex, moves the whole terrain around.
+    pub offset: [f32; 3]
+}
+
+impl Default for TerrainDesc {
+    fn default() -> TerrainDesc {
+        TerrainDesc {
+            heightmap: PathBuf::from("./fixtures/97295-mountain2-height-map-merged.png"),
+            resolution: 500,
+            cell_size: 0.15,
+            height_scale: 5.0,
+            offset: [0.0, 0.0, 0.0]
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum HeightmapError {
+    Io(PathBuf, io::Error),
+    Png(PathBuf, png::DecodingError),
+    Image(PathBuf, image::ImageError)
+}
+
+impl fmt::Display for HeightmapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HeightmapError::Io(ref path, ref err) => write!(f, "{}: {}", path.display(), err),
+            HeightmapError::Png(ref path, ref err) => write!(f, "{}: {}", path.display(), err),
+            HeightmapError::Image(ref path, ref err) => write!(f, "{}: {}", path.display(), err)
+        }
+    }
+}
+
+// Single channel heightmap, row by row. 8-bit sources are widened so 255 maps to 65535.
+#[derive(Debug, Clone)]
+pub struct Heightmap {
+    pub width: u32,
+    pub height: u32,
+    pub samples: Vec<u16>
+}
+
+// PNGs are read with their full bit depth, 8 and 16-bit grayscale alike. Coloured images use
+// their first channel. Other formats go through `image` and are converted to 8-bit grayscale.
+pub fn load_heightmap<P: AsRef<Path>>(path: P) -> Result<Heightmap, HeightmapError> {
+    let path = path.as_ref();
+    let is_png = path.extension().map(|ext| ext.eq_ignore_ascii_case("png")).unwrap_or(false);
+    if !is_png {
+        let luma = image::open(path).map_err(|err| HeightmapError::Image(path.to_path_buf(), err))?.to_luma();
+        let (width, height) = luma.dimensions();
+        return Ok(Heightmap {
+            width: width,
+            height: height,
+            samples: luma.into_raw().into_iter().map(|v| v as u16 * 257).collect()
+        });
+    }
+
+    let file = File::open(path).map_err(|err| HeightmapError::Io(path.to_path_buf(), err))?;
+    let mut decoder = png::Decoder::new(file);
+    // Only expand palettes and sub-byte depths, the default would also strip 16-bit down to 8.
+    decoder.set(png::Transformations::EXPAND);
+    let (info, mut reader) = decoder.read_info().map_err(|err| HeightmapError::Png(path.to_path_buf(), err))?;
+    let mut buffer = vec![0u8; reader.output_buffer_size()];
+    reader.next_frame(&mut buffer).map_err(|err| HeightmapError::Png(path.to_path_buf(), err))?;
+
+    // After EXPAND a palette turns into RGB, or RGBA if it has a tRNS chunk, and grayscale
+    // with tRNS gains an alpha channel. Only the first channel is used.
+    let channels = reader.output_color_type().0.samples();
+    // `info.bit_depth` claims 8 bits whenever EXPAND is set, but 16-bit samples are left alone.
+    let samples = match reader.info().bit_depth {
+        // PNG stores 16-bit samples big-endian.
+        png::BitDepth::Sixteen => buffer.chunks(2 * channels).map(|p| u16::from_be_bytes([p[0], p[1]])).collect(),
+        _ => buffer.chunks(channels).map(|p| p[0] as u16 * 257).collect()
+    };
+
+    Ok(Heightmap {
+        width: info.width,
+        height: info.height,
+        samples: samples
+    })
+}
+
+// CPU copy of the terrain surface, sampled the way `vs_plane_hmap` samples the heightmap:
+// linear filtering between texel centres, repeating past the edges. Heights are world `y`
+// values, the terrain grows towards -y like everything else in the world.
+pub struct Heightfield {
+    heightmap: Heightmap,
+    resolution: u32,
+    cell_size: f32,
+    height_scale: f32,
+    offset: [f32; 3]
+}
+
+impl Heightfield {
+    pub fn new(heightmap: Heightmap, desc: &TerrainDesc) -> Heightfield {
+        Heightfield {
+            heightmap: heightmap,
+            resolution: desc.resolution,
+            cell_size: desc.cell_size,
+            height_scale: desc.height_scale,
+            offset: desc.offset
+        }
+    }
+
+    pub fn load(desc: &TerrainDesc) -> Result<Heightfield, HeightmapError> {
+        Ok(Heightfield::new(load_heightmap(&desc.heightmap)?, desc))
+    }
+
+    // Length of the grid along +x and -z, it has one quad more than `resolution`.
+    pub fn extent(&self) -> f32 {
+        self.cell_size * (self.resolution + 1) as f32
+    }
+
+    pub fn contains(&self, x: f32, z: f32) -> bool {
+        let local_x = x - self.offset[0];
+        let local_z = self.offset[2] - z;
+        local_x >= 0.0 && local_x <= self.extent() && local_z >= 0.0 && local_z <= self.extent()
+    }
+
+    // Bilinear surface height at (x, z), `None` outside of the grid.
+    pub fn height_at(&self, x: f32, z: f32) -> Option<f32> {
+        if self.contains(x, z) { Some(self.surface_y(x, z)) } else { None }
+    }
+
+    // Unit normal pointing away from the ground (towards -y), from central differences.
+    pub fn normal_at(&self, x: f32, z: f32) -> Option<Vector3<f32>> {
+        if !self.contains(x, z) {
+            return None;
+        }
+        let e = self.cell_size * 0.5;
+        let dx = (self.surface_y(x + e, z) - self.surface_y(x - e, z)) / (2.0 * e);
+        let dz = (self.surface_y(x, z + e) - self.surface_y(x, z - e)) / (2.0 * e);
+        Some(Vector3::new(dx, -1.0, dz).normalize())
+    }
+
+    // First point where the ray enters the ground within `max_distance`, marching half a cell
+    // at a time and refining the crossing by bisection.
+    pub fn intersect_ray(&self, origin: Point3<f32>, direction: Vector3<f32>, max_distance: f32) -> Option<Point3<f32>> {
+        let direction = direction.normalize();
+        let below = |t: f32| {
+            let p = origin + direction * t;
+            self.contains(p.x, p.z) && p.y >= self.surface_y(p.x, p.z)
+        };
+        if below(0.0) {
+            return Some(origin);
+        }
+
+        let step = self.cell_size * 0.5;
+        let mut previous = 0.0;
+        while previous < max_distance {
+            let t = (previous + step).min(max_distance);
+            if below(t) {
+                let (mut above_t, mut below_t) = (previous, t);
+                for _ in 0 .. 16 {
+                    let middle = (above_t + below_t) * 0.5;
+                    if below(middle) { below_t = middle } else { above_t = middle }
+                }
+                return Some(origin + direction * below_t);
+            }
+            previous = t;
+        }
+        None
+    }
+
+    fn surface_y(&self, x: f32, z: f32) -> f32 {
+        let size = self.cell_size * self.resolution as f32;
+        let u = (x - self.offset[0]) / size;
+        let v = (self.offset[2] - z) / size;
+        -self.sample(u, v) * self.height_scale + self.offset[1]
+    }
+
+    // Normalized height at texture coordinates (u, v), like a linear `Repeat` sampler.
+    fn sample(&self, u: f32, v: f32) -> f32 {
+        let tx = u * self.heightmap.width as f32 - 0.5;
+        let ty = v * self.heightmap.height as f32 - 0.5;
+        let (x0, y0) = (tx.floor(), ty.floor());
+        let (fx, fy) = (tx - x0, ty - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = self.texel(x0, y0) * (1.0 - fx) + self.texel(x0 + 1, y0) * fx;
+        let bottom = self.texel(x0, y0 + 1) * (1.0 - fx) + self.texel(x0 + 1, y0 + 1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+
+    fn texel(&self, x: i64, y: i64) -> f32 {
+        let width = self.heightmap.width as i64;
+        let height = self.heightmap.height as i64;
+        let index = y.rem_euclid(height) * width + x.rem_euclid(width);
+        self.heightmap.samples[index as usize] as f32 / 65535.0
+    }
+}
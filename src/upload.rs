@@ -0,0 +1,84 @@
+use std::sync::Arc;
+use vulkano::buffer::BufferAccess;
+use vulkano::buffer::BufferUsage;
+use vulkano::buffer::cpu_access::CpuAccessibleBuffer;
+use vulkano::buffer::immutable::ImmutableBuffer;
+use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBuffer};
+use vulkano::device::Queue;
+use vulkano::format::{AcceptsPixels, Format, FormatDesc};
+use vulkano::image::{Dimensions, ImageLayout, ImageUsage, MipmapsCount};
+use vulkano::image::immutable::ImmutableImage;
+use vulkano::instance::QueueFamily;
+use vulkano::sync::GpuFuture;
+
+use device_selection::Queues;
+use error::VulrustError;
+
+
+// Buffer or image and the future that fills it.
+pub type Upload<T> = (Arc<ImmutableBuffer<[T]>>, Box<GpuFuture + Send + Sync>);
+pub type ImageUpload<F> = (Arc<ImmutableImage<F>>, Box<GpuFuture + Send + Sync>);
+
+// Copies static geometry and textures into device-local memory on the transfer queue. What
+// it creates is drawn from on the graphics queue, so it's shared concurrently between the
+// two families whenever they differ.
+#[derive(Debug, Clone)]
+pub struct Uploads {
+    pub queue: Arc<Queue>,
+    // See `QueueFamilies::upload_families`.
+    pub families: Vec<u32>
+}
+
+impl Uploads {
+    pub fn new(queues: &Queues) -> Uploads {
+        Uploads {
+            queue: queues.transfer.clone(),
+            families: queues.families.upload_families()
+        }
+    }
+
+    fn queue_families(&self) -> Vec<QueueFamily<'_>> {
+        let physical = self.queue.device().physical_device();
+        self.families.iter().filter_map(|&id| physical.queue_family_by_id(id)).collect()
+    }
+
+    // Like `ImmutableBuffer::from_iter`, which shares with every family of the device.
+    // The buffer can't be used before the returned future has run, so join it into the first frame.
+    pub fn buffer<T, I>(&self, data: I, usage: BufferUsage) -> Result<Upload<T>, VulrustError>
+        where T: Send + Sync + 'static,
+              I: ExactSizeIterator<Item = T>
+    {
+        let device = self.queue.device().clone();
+        let source = CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::transfer_source(), data)?;
+        let usage = BufferUsage { transfer_destination: true, .. usage };
+        // Sized after `source` and filled from it before anything else gets to read it.
+        let (buffer, init) = unsafe { ImmutableBuffer::raw(device.clone(), source.size(), usage, self.queue_families())? };
+
+        let command_buffer = AutoCommandBufferBuilder::primary_one_time_submit(device, self.queue.family())?
+            .copy_buffer(source, init)?
+            .build()?;
+        Ok((buffer, Box::new(command_buffer.execute(self.queue.clone())?) as Box<GpuFuture + Send + Sync>))
+    }
+
+    // Like `ImmutableImage::from_iter`, a sampled image without mipmaps.
+    pub fn image<P, I, F>(&self, pixels: I, dimensions: Dimensions, format: F) -> Result<ImageUpload<F>, VulrustError>
+        where P: Send + Sync + Clone + 'static,
+              I: ExactSizeIterator<Item = P>,
+              F: FormatDesc + AcceptsPixels<P> + Send + Sync + 'static,
+              Format: AcceptsPixels<P>
+    {
+        let device = self.queue.device().clone();
+        let source = CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::transfer_source(), pixels)?;
+        let usage = ImageUsage {
+            transfer_destination: true,
+            sampled: true,
+            .. ImageUsage::none()
+        };
+        let (image, init) = ImmutableImage::uninitialized(device.clone(), dimensions, format, MipmapsCount::One, usage, ImageLayout::ShaderReadOnlyOptimal, self.queue_families())?;
+
+        let command_buffer = AutoCommandBufferBuilder::primary_one_time_submit(device, self.queue.family())?
+            .copy_buffer_to_image(source, init)?
+            .build()?;
+        Ok((image, Box::new(command_buffer.execute(self.queue.clone())?) as Box<GpuFuture + Send + Sync>))
+    }
+}
@@ -0,0 +1,185 @@
+use std::env;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use vulkano::instance::{Instance, InstanceExtensions};
+use vulkano::instance::debug::{DebugCallback, MessageTypes};
+use vulkano::instance::layers_list;
+
+use error::VulrustError;
+
+
+pub const VALIDATION_ENV_VAR: &str = "VULRUST_VALIDATION";
+pub const VALIDATION_LAYER: &str = "VK_LAYER_KHRONOS_validation";
+
+// Set with `--validation [on|strict]` or `VULRUST_VALIDATION`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValidationMode {
+    Off,
+    // Validation layer and debug callback, messages are only logged.
+    On,
+    // Like `On`, but the run fails if the layer reported any error.
+    Strict
+}
+
+impl ValidationMode {
+    pub fn parse(value: &str) -> Option<ValidationMode> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "0" | "off" => Some(ValidationMode::Off),
+            "1" | "on" => Some(ValidationMode::On),
+            "strict" => Some(ValidationMode::Strict),
+            _ => None
+        }
+    }
+
+    // Off when `VULRUST_VALIDATION` isn't set.
+    pub fn from_env() -> Result<ValidationMode, VulrustError> {
+        match env::var(VALIDATION_ENV_VAR) {
+            Ok(value) => ValidationMode::parse(&value).ok_or_else(|| VulrustError::invalid_argument(VALIDATION_ENV_VAR, &value)),
+            Err(_) => Ok(ValidationMode::Off)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Debug,
+    Info,
+    Performance,
+    Warning,
+    Error
+}
+
+impl Severity {
+    // The most severe of the flags a message came with.
+    pub fn of(ty: MessageTypes) -> Severity {
+        if ty.error {
+            Severity::Error
+        } else if ty.warning {
+            Severity::Warning
+        } else if ty.performance_warning {
+            Severity::Performance
+        } else if ty.information {
+            Severity::Info
+        } else {
+            Severity::Debug
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Severity::Debug => "debug",
+            Severity::Info => "info",
+            Severity::Performance => "performance warning",
+            Severity::Warning => "warning",
+            Severity::Error => "error"
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Logs what the debug callback reports and counts errors and warnings.
+#[derive(Debug, Default)]
+pub struct ValidationLog {
+    errors: AtomicUsize,
+    warnings: AtomicUsize
+}
+
+impl ValidationLog {
+    pub fn record(&self, severity: Severity, layer_prefix: &str, description: &str) {
+        match severity {
+            Severity::Error => { self.errors.fetch_add(1, Ordering::SeqCst); },
+            Severity::Warning | Severity::Performance => { self.warnings.fetch_add(1, Ordering::SeqCst); },
+            _ => ()
+        }
+        // Errors and warnings go to stderr with the rest of the failures, the others along
+        // with the setup progress.
+        if severity >= Severity::Warning {
+            eprintln!("Vulkan {} [{}]: {}", severity, layer_prefix, description);
+        } else {
+            println!("Vulkan {} [{}]: {}", severity, layer_prefix, description);
+        }
+    }
+
+    pub fn errors(&self) -> usize {
+        self.errors.load(Ordering::SeqCst)
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.warnings.load(Ordering::SeqCst)
+    }
+}
+
+// Keeps the debug callback alive, so it has to live as long as the instance is used.
+pub struct Validation {
+    pub mode: ValidationMode,
+    // Whether `VK_LAYER_KHRONOS_validation` was found and enabled.
+    pub layer_enabled: bool,
+    pub log: Arc<ValidationLog>,
+    _callback: Option<DebugCallback>
+}
+
+impl Validation {
+    // No layer and no callback, e.g. for `ValidationMode::Off`.
+    pub fn detached(mode: ValidationMode) -> Validation {
+        Validation {
+            mode: mode,
+            layer_enabled: false,
+            log: Arc::new(ValidationLog::default()),
+            _callback: None
+        }
+    }
+
+    // Fails in strict mode once the layer reported an error.
+    pub fn check(&self) -> Result<(), VulrustError> {
+        let errors = self.log.errors();
+        if self.mode == ValidationMode::Strict && errors > 0 {
+            return Err(VulrustError::Validation(errors));
+        }
+        Ok(())
+    }
+}
+
+// Creates the instance with `extensions`. Unless `mode` is off, also enables the validation
+// layer if it's installed and routes the messages of the debug report extension into a
+// `ValidationLog`. In strict mode, both are required.
+pub fn create_instance(extensions: InstanceExtensions, mode: ValidationMode) -> Result<(Arc<Instance>, Validation), VulrustError> {
+    let mut validation = Validation::detached(mode);
+    if mode == ValidationMode::Off {
+        return Ok((Instance::new(None, &extensions, None)?, validation));
+    }
+
+    validation.layer_enabled = layers_list()
+        .map(|mut layers| layers.any(|layer| layer.name() == VALIDATION_LAYER))
+        .unwrap_or(false);
+    if !validation.layer_enabled {
+        if mode == ValidationMode::Strict {
+            return Err(VulrustError::ValidationUnavailable(VALIDATION_LAYER));
+        }
+        eprintln!("{} isn't installed, running without validation.", VALIDATION_LAYER);
+    }
+    let layers = if validation.layer_enabled { vec![VALIDATION_LAYER] } else { Vec::new() };
+
+    let supported = InstanceExtensions::supported_by_core()?;
+    if !supported.ext_debug_report && mode == ValidationMode::Strict {
+        return Err(VulrustError::ValidationUnavailable("VK_EXT_debug_report"));
+    }
+    let extensions = InstanceExtensions { ext_debug_report: supported.ext_debug_report, .. extensions };
+    let instance = Instance::new(None, &extensions, layers)?;
+
+    if extensions.ext_debug_report {
+        let log = validation.log.clone();
+        // Everything, the info and debug messages are logged too.
+        let types = MessageTypes { information: true, debug: true, .. MessageTypes::errors_and_warnings() };
+        let callback = DebugCallback::new(&instance, types, move |message| {
+            log.record(Severity::of(message.ty), message.layer_prefix, message.description);
+        })?;
+        validation._callback = Some(callback);
+    } else {
+        eprintln!("VK_EXT_debug_report isn't supported, validation messages won't be logged.");
+    }
+    Ok((instance, validation))
+}
@@ -11,6 +11,14 @@ pub struct Vertex3DNormal3D {
 }
 impl_vertex!(Vertex3DNormal3D, position, normal);
 
+#[derive(Debug, Clone)]
+pub struct Vertex3DNormal3DUV {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2]
+}
+impl_vertex!(Vertex3DNormal3DUV, position, normal, uv);
+
 #[derive(Debug, Clone)]
 pub struct Vertex3DColor3D {
     pub instance_position: [f32; 3],
@@ -1,45 +1,211 @@
-use cgmath::{Matrix4, Vector4, Rad, InnerSpace};
+use cgmath::{Point3, Vector3, Matrix4, Rad, InnerSpace, perspective};
 
+use camera;
+use camera::Camera;
+use global::*;
+use math_utils::vulkan_clip;
+use terrain::Heightfield;
 
-const DEFAULT_DIRECTION: Vector4<f32> = Vector4 {
-    x: 0.0,
-    y: 0.0,
-    z: 1.0,
-    w: 1.0
-};
+
+const FOVY: Rad<f32> = Rad(1.4);
+const MIN_FOVY: Rad<f32> = Rad(0.2);
+const MAX_FOVY: Rad<f32> = Rad(2.5);
+const Z_NEAR: f32 = 0.01;
+const Z_FAR: f32 = 100.0;
+
+// Speeds are per second, every movement takes the seconds the frame covered.
+const MOVE_SPEED: f32 = 12.0;
+const MIN_MOVE_SPEED: f32 = 0.5;
+const MAX_MOVE_SPEED: f32 = 300.0;
+const TURN_SPEED: Rad<f32> = Rad(1.2);
+// Spin of the rectangles, in `delta` units.
+const SPIN_SPEED: f32 = 120.0;
+
+// Walk mode: how far above the ground the eye stays, how fast it catches up with the ground
+// (the remaining height difference shrinks by e every 1 / WALK_SMOOTHING seconds), and the
+// steepest slope that can still be climbed.
+const EYE_HEIGHT: f32 = 1.0;
+const WALK_SMOOTHING: f32 = 12.0;
+const MAX_SLOPE: Rad<f32> = Rad(0.8);
 
 pub struct World {
     pub projection: Matrix4<f32>,
-    pub view: Matrix4<f32>,
-    pub model: Matrix4<f32>,
-    pub direction_angle: f32
+    pub camera: Camera,
+    // Distance covered in a second of movement.
+    pub move_speed: f32,
+    fovy: Rad<f32>,
+    aspect: f32,
+    // Animation step of the spinning rectangles.
+    pub delta: f32,
+    // Terrain to walk on, walking does nothing without it.
+    pub ground: Option<Heightfield>,
+    pub walking: bool,
+    // State at the start of the latest fixed update, frames are drawn `alpha` of the way
+    // from it to the current state. Only the position and the spin are blended, the view
+    // direction follows the mouse straight away.
+    previous_position: Point3<f32>,
+    previous_delta: f32,
+    pub alpha: f32
 }
 
 impl World {
-    pub fn move_forwards(&mut self) {
-        self._move(0.2);
+    pub fn new() -> World {
+        let camera = Camera::look_at(Point3::new(2.0, -6.0, 7.0), Point3::new(2.0, 0.0, 0.0));
+        World {
+            projection: vulkan_clip() * perspective(FOVY, SCR_WIDTH / SCR_HEIGHT, Z_NEAR, Z_FAR),
+            camera: camera,
+            move_speed: MOVE_SPEED,
+            fovy: FOVY,
+            aspect: SCR_WIDTH / SCR_HEIGHT,
+            delta: 0.0,
+            ground: None,
+            walking: false,
+            previous_position: camera.position,
+            previous_delta: 0.0,
+            alpha: 1.0
+        }
+    }
+
+    pub fn set_aspect_ratio(&mut self, aspect: f32) {
+        self.aspect = aspect;
+        self.projection = vulkan_clip() * perspective(self.fovy, self.aspect, Z_NEAR, Z_FAR);
+    }
+
+    pub fn fovy(&self) -> Rad<f32> {
+        self.fovy
+    }
+
+    pub fn set_fovy(&mut self, fovy: Rad<f32>) {
+        self.fovy = if fovy < MIN_FOVY { MIN_FOVY } else if fovy > MAX_FOVY { MAX_FOVY } else { fovy };
+        self.projection = vulkan_clip() * perspective(self.fovy, self.aspect, Z_NEAR, Z_FAR);
+    }
+
+    // Positive steps narrow the field of view, like zooming in.
+    pub fn zoom(&mut self, steps: f32) {
+        let fovy = self.fovy - Rad(0.05 * steps);
+        self.set_fovy(fovy);
+    }
+
+    // Every step makes movement 10% faster, negative ones slower.
+    pub fn scale_move_speed(&mut self, steps: f32) {
+        let move_speed = self.move_speed * 1.1f32.powf(steps);
+        self.move_speed = move_speed.clamp(MIN_MOVE_SPEED, MAX_MOVE_SPEED);
+    }
+
+    pub fn animate(&mut self, dt: f32) {
+        self.delta += SPIN_SPEED * dt;
+    }
+
+    // The only matrix the shaders get, world space straight to clip space.
+    pub fn view_projection(&self) -> Matrix4<f32> {
+        let mut camera = self.camera;
+        camera.position = self.previous_position + (self.camera.position - self.previous_position) * self.alpha;
+        self.projection * camera.view()
+    }
+
+    // Remembers the state to blend from, call it before every fixed update.
+    pub fn begin_tick(&mut self) {
+        self.previous_position = self.camera.position;
+        self.previous_delta = self.delta;
+    }
+
+    // `delta` blended like the view.
+    pub fn spin(&self) -> f32 {
+        self.previous_delta + (self.delta - self.previous_delta) * self.alpha
+    }
+
+    pub fn move_forwards(&mut self, dt: f32) {
+        let direction = self.heading();
+        self._move(direction * self.move_speed * dt);
+    }
+
+    pub fn move_backwards(&mut self, dt: f32) {
+        let direction = self.heading();
+        self._move(direction * -self.move_speed * dt);
+    }
+
+    pub fn strafe_left(&mut self, dt: f32) {
+        let direction = self.camera.right();
+        self._move(direction * -self.move_speed * dt);
+    }
+
+    pub fn strafe_right(&mut self, dt: f32) {
+        let direction = self.camera.right();
+        self._move(direction * self.move_speed * dt);
+    }
+
+    // Flying only, walking keeps the eye on the ground.
+    pub fn move_up(&mut self, dt: f32) {
+        if !self.walking {
+            self.camera.translate(camera::UP * self.move_speed * dt);
+        }
+    }
+
+    pub fn move_down(&mut self, dt: f32) {
+        if !self.walking {
+            self.camera.translate(camera::UP * -self.move_speed * dt);
+        }
+    }
+
+    pub fn turn_left(&mut self, dt: f32) {
+        self.camera.turn(-TURN_SPEED * dt);
+    }
+
+    pub fn turn_right(&mut self, dt: f32) {
+        self.camera.turn(TURN_SPEED * dt);
+    }
+
+    pub fn look_up(&mut self, dt: f32) {
+        self.camera.look_up(TURN_SPEED * dt);
+    }
+
+    pub fn look_down(&mut self, dt: f32) {
+        self.camera.look_up(-TURN_SPEED * dt);
+    }
+
+    pub fn toggle_walking(&mut self) {
+        self.walking = !self.walking;
     }
 
-    pub fn move_backwards(&mut self) {
-        self._move(-0.2);
+    pub fn eye_position(&self) -> Point3<f32> {
+        self.camera.position
     }
 
-    pub fn rotate_clockwise(&mut self) {
-        self._rotate(0.02);
+    // Eases the eye towards `EYE_HEIGHT` above the ground, call it once per fixed update with
+    // the tick length.
+    pub fn follow_ground(&mut self, dt: f32) {
+        if !self.walking {
+            return;
+        }
+        let eye = self.camera.position;
+        let ground = match self.ground_height(eye) {
+            Some(ground) => ground,
+            None => return
+        };
+        // The terrain rises towards -y, so the eye sits below the ground height.
+        let target = ground - EYE_HEIGHT;
+        self.camera.position.y += (target - eye.y) * (1.0 - (-WALK_SMOOTHING * dt).exp());
     }
 
-    pub fn rotate_counterclockwise(&mut self) {
-        self._rotate(-0.02);
+    // Walking goes along the ground whatever the pitch, flying goes where the camera looks.
+    fn heading(&self) -> Vector3<f32> {
+        if self.walking { self.camera.flat_forward() } else { self.camera.forward() }
     }
 
-    fn _move(&mut self, k: f32) {
-        let mut direction = (Matrix4::from_angle_y(Rad(self.direction_angle)) * DEFAULT_DIRECTION).truncate().normalize() * k;
-        direction.x *= -1.0;
-        self.model = self.model * Matrix4::from_translation(direction);
+    fn ground_height(&self, eye: Point3<f32>) -> Option<f32> {
+        self.ground.as_ref().and_then(|ground| ground.height_at(eye.x, eye.z))
     }
 
-    fn _rotate(&mut self, k: f32) {
-        self.direction_angle += k;
-        self.model = Matrix4::from_angle_y(Rad(k)) * self.model;
+    fn _move(&mut self, offset: Vector3<f32>) {
+        if self.walking {
+            let (from, to) = (self.camera.position, self.camera.position + offset);
+            if let (Some(from_ground), Some(to_ground)) = (self.ground_height(from), self.ground_height(to)) {
+                let rise = from_ground - to_ground;
+                if rise > offset.magnitude() * MAX_SLOPE.0.tan() {
+                    return;
+                }
+            }
+        }
+        self.camera.translate(offset);
     }
 }
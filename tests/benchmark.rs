@@ -0,0 +1,43 @@
+extern crate vulrust;
+
+use std::time::Duration;
+
+use vulrust::benchmark::FrameStats;
+
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+#[test]
+fn skips_warmup_frames() {
+    let mut stats = FrameStats::new(2);
+    stats.record(ms(100));
+    stats.record(ms(100));
+    assert!(stats.is_empty());
+
+    stats.record(ms(4));
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats.mean(), ms(4));
+}
+
+#[test]
+fn summarizes_frame_times() {
+    let mut stats = FrameStats::new(0);
+    for millis in &[5, 1, 4, 2, 3] {
+        stats.record(ms(*millis));
+    }
+    assert_eq!(stats.mean(), ms(3));
+    assert_eq!(stats.percentile(0.0), ms(1));
+    assert_eq!(stats.percentile(0.5), ms(3));
+    assert_eq!(stats.percentile(0.99), ms(5));
+    assert_eq!(stats.percentile(1.0), ms(5));
+    assert_eq!(stats.to_string(), "5 frames, CPU frame time mean 3.000 ms, median 3.000 ms, 99th percentile 5.000 ms, max 5.000 ms");
+}
+
+#[test]
+fn empty_stats_are_zero() {
+    let stats = FrameStats::new(0);
+    assert_eq!(stats.mean(), ms(0));
+    assert_eq!(stats.percentile(0.5), ms(0));
+}
@@ -0,0 +1,73 @@
+extern crate cgmath;
+extern crate vulrust;
+
+use cgmath::{InnerSpace, Point3, Rad, Transform, Vector3, Vector4};
+
+use vulrust::camera::{Camera, UP};
+use vulrust::world::World;
+
+
+fn close(a: Vector3<f32>, b: Vector3<f32>) -> bool {
+    (a - b).magnitude() < 1e-5
+}
+
+#[test]
+fn looks_along_minus_z_by_default() {
+    let camera = Camera::new(Point3::new(0.0, 0.0, 0.0), Rad(0.0), Rad(0.0));
+
+    assert!(close(camera.forward(), Vector3::new(0.0, 0.0, -1.0)));
+    // World up is -y, so with a right-handed world the right hand side is -x.
+    assert!(close(camera.right(), Vector3::new(-1.0, 0.0, 0.0)));
+    assert!(close(camera.forward().cross(camera.right()), -UP));
+}
+
+#[test]
+fn look_at_recovers_yaw_and_pitch() {
+    let target = Point3::new(3.0, -1.0, 2.0);
+    let camera = Camera::look_at(Point3::new(1.0, 2.0, 5.0), target);
+
+    assert!(close(camera.forward(), (target - camera.position).normalize()));
+    assert!(camera.pitch.0 > 0.0, "the target is above, got pitch {:?}", camera.pitch);
+}
+
+#[test]
+fn view_puts_the_eye_at_the_origin() {
+    let mut camera = Camera::look_at(Point3::new(1.0, -2.0, 3.0), Point3::new(0.0, 0.0, 0.0));
+    camera.turn(Rad(0.3));
+    camera.look_up(Rad(0.2));
+    camera.tilt(Rad(0.1));
+
+    let view = camera.view();
+    let eye = view.transform_point(camera.position);
+    assert!(close(Vector3::new(eye.x, eye.y, eye.z), Vector3::new(0.0, 0.0, 0.0)));
+    let ahead = view.transform_point(camera.position + camera.forward());
+    assert!(close(Vector3::new(ahead.x, ahead.y, ahead.z), Vector3::new(0.0, 0.0, -1.0)));
+}
+
+#[test]
+fn pitch_is_clamped() {
+    let mut camera = Camera::new(Point3::new(0.0, 0.0, 0.0), Rad(0.0), Rad(0.0));
+    camera.look_up(Rad(10.0));
+    assert!(camera.pitch.0 < 1.571);
+    camera.look_up(Rad(-20.0));
+    assert!(camera.pitch.0 > -1.571);
+}
+
+#[test]
+fn view_projection_keeps_up_at_the_top_of_the_screen() {
+    let mut world = World::new();
+    world.set_aspect_ratio(1.0);
+    world.camera = Camera::new(Point3::new(0.0, 0.0, 0.0), Rad(0.0), Rad(0.0));
+
+    let clip = |p: Point3<f32>| {
+        let c = world.view_projection() * Vector4::new(p.x, p.y, p.z, 1.0);
+        Vector3::new(c.x / c.w, c.y / c.w, c.z / c.w)
+    };
+    let ahead = clip(Point3::new(0.0, 0.0, -5.0));
+    assert!(close(Vector3::new(ahead.x, ahead.y, 0.0), Vector3::new(0.0, 0.0, 0.0)));
+    assert!(ahead.z > 0.0 && ahead.z < 1.0);
+
+    // Vulkan's clip space has y pointing down the screen.
+    assert!(clip(Point3::new(0.0, -1.0, -5.0)).y < 0.0);
+    assert!(clip(Point3::new(-1.0, 0.0, -5.0)).x > 0.0);
+}
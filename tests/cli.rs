@@ -0,0 +1,45 @@
+// Command line handling of the binary. Bad options are rejected before any Vulkan setup, so
+// these run without a device.
+use std::process::{Command, Output};
+
+
+fn run(args: &[&str], env: &[(&str, &str)]) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_vulrust"));
+    command.current_dir(env!("CARGO_MANIFEST_DIR")).args(args);
+    for &(name, value) in env {
+        command.env(name, value);
+    }
+    command.output().unwrap()
+}
+
+fn assert_rejected(output: &Output, message: &str) {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1), "{}", stdout);
+    assert!(stdout.contains(message), "expected {:?} in {:?}", message, stdout);
+}
+
+#[test]
+fn rejects_bad_arguments() {
+    let output = run(&["--headless", "unused.png", "--seed", "abc"], &[]);
+    assert_rejected(&output, "Error: invalid value \"abc\" for --seed");
+
+    let output = run(&["--headless", "unused.png", "--scene", "teapot"], &[]);
+    assert_rejected(&output, "Error: invalid value \"teapot\" for --scene");
+
+    // Checked before the window opens.
+    for rate in &["0", "-60", "inf", "NaN"] {
+        let output = run(&["--tick-rate", rate], &[]);
+        assert_rejected(&output, &format!("Error: invalid value {:?} for --tick-rate", rate));
+    }
+
+    for count in &["0", "-1", "two"] {
+        let output = run(&["--frames-in-flight", count], &[]);
+        assert_rejected(&output, &format!("Error: invalid value {:?} for --frames-in-flight", count));
+    }
+}
+
+#[test]
+fn rejects_bad_environment_variables() {
+    let output = run(&["--headless", "unused.png"], &[("VULRUST_VALIDATION", "loud")]);
+    assert_rejected(&output, "Error: invalid value \"loud\" for VULRUST_VALIDATION");
+}
@@ -0,0 +1,148 @@
+extern crate vulkano;
+extern crate vulrust;
+
+use vulkano::format::Format;
+use vulkano::instance::{PhysicalDeviceType, Version};
+
+use vulrust::device_selection::{choose, DeviceInfo, DevicePreference, DeviceRequirements, FormatFeatures, QueueFamilies, QueueFamilyInfo, Unsuitable};
+use vulrust::error::VulrustError;
+
+
+fn family(id: u32, graphics: bool, transfers: bool, present: bool) -> QueueFamilyInfo {
+    QueueFamilyInfo { id: id, queues: 1, graphics: graphics, compute: graphics, transfers: transfers, present: present }
+}
+
+fn device(index: usize, name: &str, ty: PhysicalDeviceType, problems: Vec<Unsuitable>) -> DeviceInfo {
+    DeviceInfo {
+        index: index,
+        name: name.to_string(),
+        ty: ty,
+        api_version: Version { major: 1, minor: 1, patch: 0 },
+        driver_version: 1,
+        vendor_id: 0,
+        device_id: 0,
+        device_memory: 1 << 30,
+        queue_families: vec![QueueFamilyInfo { id: 0, queues: 1, graphics: true, compute: true, transfers: true, present: true }],
+        assigned: Some(QueueFamilies { graphics: 0, present: 0, transfer: 0 }),
+        problems: problems
+    }
+}
+
+fn machine() -> Vec<DeviceInfo> {
+    vec![
+        device(0, "llvmpipe (LLVM 15.0.7, 256 bits)", PhysicalDeviceType::Cpu, vec![]),
+        device(1, "Intel(R) UHD Graphics 620", PhysicalDeviceType::IntegratedGpu, vec![]),
+        device(2, "NVIDIA GeForce GTX 1050", PhysicalDeviceType::DiscreteGpu, vec![Unsuitable::CannotPresent])
+    ]
+}
+
+#[test]
+fn parses_preferences() {
+    assert_eq!(DevicePreference::parse(""), DevicePreference::Auto);
+    assert_eq!(DevicePreference::parse("Auto"), DevicePreference::Auto);
+    assert_eq!(DevicePreference::parse(" 2 "), DevicePreference::Index(2));
+    assert_eq!(DevicePreference::parse("nvidia"), DevicePreference::Name("nvidia".to_string()));
+}
+
+#[test]
+fn prefers_the_best_suitable_device() {
+    let devices = machine();
+    assert_eq!(choose(&devices, &DevicePreference::Auto).unwrap().index, 1);
+
+    // Same kind and memory, the first one wins.
+    let devices = vec![
+        device(0, "first", PhysicalDeviceType::DiscreteGpu, vec![]),
+        device(1, "second", PhysicalDeviceType::DiscreteGpu, vec![])
+    ];
+    assert_eq!(choose(&devices, &DevicePreference::Auto).unwrap().index, 0);
+}
+
+#[test]
+fn honours_overrides() {
+    let devices = machine();
+    assert_eq!(choose(&devices, &DevicePreference::Index(0)).unwrap().index, 0);
+    assert_eq!(choose(&devices, &DevicePreference::Name("LLVMPIPE".to_string())).unwrap().index, 0);
+
+    match choose(&devices, &DevicePreference::Name("geforce".to_string())) {
+        Err(VulrustError::UnsuitableDevice(name, problems)) => {
+            assert_eq!(name, "NVIDIA GeForce GTX 1050");
+            assert_eq!(problems, vec![Unsuitable::CannotPresent]);
+        },
+        other => panic!("expected an unsuitable device, got {:?}", other.map(|device| device.index))
+    }
+    match choose(&devices, &DevicePreference::Index(7)) {
+        Err(VulrustError::DeviceNotFound(preference)) => assert_eq!(preference, "7"),
+        other => panic!("expected no device, got {:?}", other.map(|device| device.index))
+    }
+}
+
+#[test]
+fn fails_without_suitable_devices() {
+    let devices = vec![device(0, "no graphics", PhysicalDeviceType::Other, vec![Unsuitable::NoGraphicsQueue])];
+    match choose(&devices, &DevicePreference::Auto) {
+        Err(VulrustError::NoPhysicalDevice) => (),
+        other => panic!("expected no suitable device, got {:?}", other.map(|device| device.index))
+    }
+}
+
+#[test]
+fn assigns_queue_families() {
+    // One family for everything, like most integrated GPUs.
+    let families = vec![family(0, true, true, true)];
+    assert_eq!(QueueFamilies::assign(&families, true), Some(QueueFamilies { graphics: 0, present: 0, transfer: 0 }));
+    assert_eq!(QueueFamilies::assign(&families, true).unwrap().unique(), vec![0]);
+
+    // A dedicated transfer family is used for uploads, presenting stays with drawing.
+    let families = vec![family(0, true, true, true), family(1, false, false, true), family(2, false, true, false)];
+    let assigned = QueueFamilies::assign(&families, true).unwrap();
+    assert_eq!(assigned, QueueFamilies { graphics: 0, present: 0, transfer: 2 });
+    assert_eq!(assigned.unique(), vec![0, 2]);
+
+    // Drawing and presenting on different families.
+    let families = vec![family(0, true, true, false), family(1, false, false, true)];
+    let assigned = QueueFamilies::assign(&families, true).unwrap();
+    assert_eq!(assigned, QueueFamilies { graphics: 0, present: 1, transfer: 0 });
+    assert_eq!(assigned.unique(), vec![0, 1]);
+    assert_eq!(QueueFamilies::assign(&families, false).unwrap().present, 0);
+
+    assert_eq!(QueueFamilies::assign(&[family(0, true, true, false)], true), None);
+    assert_eq!(QueueFamilies::assign(&[family(0, false, true, true)], true), None);
+}
+
+#[test]
+fn uploads_are_shared_with_the_graphics_family() {
+    // A transfer-only family wins over one that also computes.
+    let compute = QueueFamilyInfo { compute: true, .. family(1, false, true, false) };
+    let families = vec![family(0, true, true, true), compute, family(2, false, true, false)];
+    let assigned = QueueFamilies::assign(&families, true).unwrap();
+    assert_eq!(assigned.transfer, 2);
+    assert_eq!(assigned.upload_families(), vec![2, 0]);
+
+    // Without one, uploads stay on the graphics family and aren't shared.
+    let assigned = QueueFamilies::assign(&[family(0, true, true, true)], true).unwrap();
+    assert_eq!(assigned.upload_families(), vec![0]);
+}
+
+#[test]
+fn checks_format_features() {
+    let vulkan_1_0 = Version { major: 1, minor: 0, patch: 0 };
+    let vulkan_1_1 = Version { major: 1, minor: 1, patch: 0 };
+    let heightmap = FormatFeatures { sampled_image: true, sampled_image_filter_linear: true, .. FormatFeatures::default() };
+
+    // Sampled, linear filter, color attachment and transfer source.
+    let all = FormatFeatures::from_bits(0x1 | 0x1000 | 0x80 | 0x4000, vulkan_1_1);
+    assert!(all.superset_of(&heightmap));
+    // Sampled only, like R16Unorm on some hardware.
+    let nearest = FormatFeatures::from_bits(0x1, vulkan_1_1);
+    assert!(!nearest.superset_of(&heightmap));
+    assert!(!FormatFeatures::from_bits(0, vulkan_1_1).superset_of(&heightmap));
+
+    // Copying out without the bit is fine before Vulkan 1.1 only.
+    let color = FormatFeatures { color_attachment: true, transfer_source: true, .. FormatFeatures::default() };
+    assert!(FormatFeatures::from_bits(0x80, vulkan_1_0).superset_of(&color));
+    assert!(!FormatFeatures::from_bits(0x80, vulkan_1_1).superset_of(&color));
+
+    let formats: Vec<Format> = DeviceRequirements::headless().formats.iter().map(|&(format, _)| format).collect();
+    assert!(formats.contains(&Format::R16Unorm));
+    assert_eq!(Unsuitable::UnsupportedFormat(Format::R16Unorm).to_string(), "no support for R16Unorm images as needed");
+}
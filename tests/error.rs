@@ -0,0 +1,36 @@
+extern crate vulrust;
+
+use vulrust::error::VulrustError;
+use vulrust::gltf_loader::load_gltf;
+use vulrust::input::InputMap;
+use vulrust::rectangle_instance::RectangleInstance;
+use vulrust::terrain::load_heightmap;
+
+
+fn load_all(path: &str) -> Result<(), VulrustError> {
+    load_heightmap(path)?;
+    Ok(())
+}
+
+#[test]
+fn wraps_asset_errors() {
+    match load_all("fixtures/missing.png") {
+        Err(VulrustError::Heightmap(_)) => (),
+        other => panic!("expected a heightmap error, got {:?}", other)
+    }
+
+    let err = VulrustError::from(load_gltf("fixtures/missing.gltf").unwrap_err());
+    assert!(err.to_string().starts_with("glTF: fixtures/missing.gltf"), "{}", err);
+
+    let err = VulrustError::from(InputMap::parse("Jump = Space").unwrap_err());
+    assert_eq!(err.to_string(), "input config: line 1: unknown action `Jump`");
+}
+
+#[test]
+fn reports_unbuilt_instances() {
+    let instance = RectangleInstance::new([0.0, 0.0, 0.0], [1.0, 1.0, 1.0]);
+    match instance.get_instance_vertex() {
+        Err(VulrustError::Missing(what)) => assert_eq!(what, "instance vertex"),
+        other => panic!("expected a missing instance vertex, got {:?}", other)
+    }
+}
@@ -0,0 +1,49 @@
+extern crate vulrust;
+
+use std::time::{Duration, Instant};
+
+use vulrust::frame_timer::FrameTimer;
+
+
+#[test]
+fn ticks_measure_real_time() {
+    let mut timer = FrameTimer::new();
+    let start = Instant::now();
+    timer.tick_at(start);
+
+    let dt = timer.tick_at(start + Duration::from_millis(16));
+    assert!((dt - 0.016).abs() < 1e-6);
+    let dt = timer.tick_at(start + Duration::from_millis(20));
+    assert!((dt - 0.004).abs() < 1e-6);
+}
+
+#[test]
+fn stalls_are_clamped() {
+    let mut timer = FrameTimer::new();
+    let start = Instant::now();
+    timer.tick_at(start);
+
+    assert_eq!(timer.tick_at(start + Duration::from_secs(3)), 0.25);
+}
+
+#[test]
+fn cap_holds_frames_back() {
+    let mut timer = FrameTimer::new();
+    let start = Instant::now();
+    timer.tick_at(start);
+    assert_eq!(timer.remaining(start), Duration::from_secs(0));
+
+    timer.set_fps_cap(Some(100.0));
+    let remaining = timer.remaining(start + Duration::from_millis(4));
+    assert!(remaining > Duration::from_micros(5900) && remaining < Duration::from_micros(6100), "{:?}", remaining);
+    assert_eq!(timer.remaining(start + Duration::from_millis(12)), Duration::from_secs(0));
+}
+
+#[test]
+fn ignores_caps_without_a_frame_time() {
+    let mut timer = FrameTimer::new();
+    for fps in &[0.0, -30.0, f32::NAN, f32::INFINITY, 1e-40] {
+        timer.set_fps_cap(Some(*fps));
+        assert_eq!(timer.min_frame_time, None, "{}", fps);
+    }
+}
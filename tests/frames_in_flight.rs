@@ -0,0 +1,47 @@
+extern crate vulrust;
+
+use vulrust::drawable::FrameCache;
+use vulrust::error::VulrustError;
+use vulrust::frames_in_flight::check_count;
+
+
+#[test]
+fn builds_each_frame_once() {
+    let cache = FrameCache::new();
+    let mut builds = 0;
+    for frame in &[0, 1, 0, 1, 2, 0] {
+        let entry = cache.get_or_build(*frame, || {
+            builds += 1;
+            Ok(frame * 10)
+        }).unwrap();
+        assert_eq!(entry, frame * 10);
+    }
+    assert_eq!(builds, 3);
+    assert_eq!(cache.built(), 3);
+
+    cache.clear();
+    assert_eq!(cache.built(), 0);
+    assert_eq!(cache.get_or_build(1, || Ok(11)).unwrap(), 11);
+    assert_eq!(cache.built(), 1);
+}
+
+#[test]
+fn keeps_nothing_when_building_fails() {
+    let cache: FrameCache<u32> = FrameCache::new();
+    match cache.get_or_build(3, || Err(VulrustError::Missing("pipeline"))) {
+        Err(VulrustError::Missing("pipeline")) => (),
+        other => panic!("expected a missing pipeline, got {:?}", other)
+    }
+    assert_eq!(cache.built(), 0);
+    assert_eq!(cache.get_or_build(3, || Ok(7)).unwrap(), 7);
+}
+
+#[test]
+fn needs_at_least_one_frame_in_flight() {
+    assert_eq!(check_count(1).unwrap(), 1);
+    assert_eq!(check_count(3).unwrap(), 3);
+    match check_count(0) {
+        Err(VulrustError::InvalidArgument { ref name, ref value }) if name == "frames in flight" && value == "0" => (),
+        other => panic!("expected an invalid argument, got {:?}", other)
+    }
+}
@@ -0,0 +1,82 @@
+extern crate vulrust;
+
+use vulrust::error::VulrustError;
+use vulrust::game_loop::{is_valid_rate, FixedStep, GameLoop};
+
+
+#[test]
+fn accumulates_frame_time_into_ticks() {
+    let mut step = FixedStep::new(50.0).unwrap();
+
+    assert_eq!(step.advance(0.01), 0);
+    assert!((step.alpha() - 0.5).abs() < 1e-4);
+    assert_eq!(step.advance(0.015), 1);
+    assert!((step.alpha() - 0.25).abs() < 1e-4);
+    assert_eq!(step.advance(0.04), 2);
+    assert!((step.alpha() - 0.25).abs() < 1e-4);
+}
+
+#[test]
+fn catch_up_is_limited() {
+    let mut step = FixedStep::new(100.0).unwrap();
+
+    // A whole second behind, but only five ticks run and the backlog is dropped.
+    assert_eq!(step.advance(1.005), 5);
+    assert!(step.alpha() < 1.0);
+    assert_eq!(step.advance(0.0), 0);
+}
+
+#[test]
+fn updates_run_at_the_tick_rate_whatever_the_frame_rate() {
+    let mut game_loop = GameLoop::new(60.0).unwrap();
+    let mut simulated = 0.0;
+    let mut ticks = 0;
+
+    let mut frame_times = 0.0;
+    for i in 0 .. 200 {
+        // Uneven frames, between 4 and 30 ms.
+        let dt = 0.004 + (i % 7) as f32 * 0.0043;
+        frame_times += dt;
+        let frame = game_loop.advance(&mut simulated, dt, &mut |simulated: &mut f32, tick| {
+            *simulated += tick;
+            ticks += 1;
+        });
+        assert!(frame.alpha >= 0.0 && frame.alpha < 1.0);
+        assert_eq!(frame.dt, dt);
+    }
+    // Everything but the unfinished tick got simulated, one step at a time.
+    assert!((frame_times - simulated).abs() < 1.0 / 60.0);
+    assert!((simulated - ticks as f32 / 60.0).abs() < 1e-3);
+}
+
+#[test]
+fn run_stops_when_the_frame_says_so() {
+    let mut game_loop = GameLoop::new(1000.0).unwrap();
+    let mut frames = 0;
+
+    game_loop.run(&mut frames, |_, _| (), |frames, frame| {
+        assert!(frame.ticks <= 5);
+        *frames += 1;
+        *frames < 3
+    });
+    assert_eq!(frames, 3);
+}
+
+#[test]
+fn tick_rates_must_be_positive_and_finite() {
+    assert!(is_valid_rate(60.0));
+    assert!(is_valid_rate(0.5));
+    for &rate in &[0.0, -60.0, f32::INFINITY, f32::NAN] {
+        assert!(!is_valid_rate(rate), "{}", rate);
+    }
+}
+
+#[test]
+fn fixed_step_rejects_a_zero_rate() {
+    match FixedStep::new(0.0) {
+        Err(VulrustError::InvalidArgument { ref name, ref value }) if name == "tick rate" && value == "0" => (),
+        Err(err) => panic!("expected an invalid tick rate, got {:?}", err),
+        Ok(_) => panic!("expected an invalid tick rate")
+    }
+    assert!(GameLoop::new(f32::NAN).is_err());
+}
@@ -0,0 +1,53 @@
+extern crate vulrust;
+
+use vulrust::geometry::{cube, plane_square, IndexedMesh};
+
+
+#[test]
+fn cube_shares_vertices_within_faces() {
+    let mesh = cube();
+
+    // Four corners per face, two triangles per face.
+    assert_eq!(mesh.vertices.len(), 24);
+    assert_eq!(mesh.indices.len(), 36);
+    assert!(mesh.triangle_list_bytes() > mesh.vertex_bytes() + mesh.index_bytes());
+
+    // First triangle of the original table: corners 1, 7, 5 facing -z.
+    let first: Vec<[f32; 3]> = mesh.indices[0 .. 3].iter().map(|&i| mesh.vertices[i as usize].position).collect();
+    assert_eq!(first, vec![[0.0, 0.0, 0.0], [1.0, 1.0, 0.0], [1.0, 0.0, 0.0]]);
+    for &i in mesh.indices[0 .. 6].iter() {
+        assert_eq!(mesh.vertices[i as usize].normal, [0.0, 0.0, -1.0]);
+    }
+}
+
+#[test]
+fn plane_square_is_a_shared_grid() {
+    let mesh = plane_square(2, 0.5);
+
+    // 3 by 3 quads over a 4 by 4 vertex grid.
+    assert_eq!(mesh.vertices.len(), 16);
+    assert_eq!(mesh.indices.len(), 54);
+    assert!(mesh.indices.iter().all(|&i| (i as usize) < mesh.vertices.len()));
+
+    let corners: Vec<([f32; 3], [f32; 2])> = mesh.indices[3 .. 6].iter()
+        .map(|&i| (mesh.vertices[i as usize].position, mesh.vertices[i as usize].uv))
+        .collect();
+    assert_eq!(corners, vec![
+        ([0.0, 0.0, -0.5], [0.0, 0.5]),
+        ([0.5, 0.0, 0.0], [0.5, 0.0]),
+        ([0.5, 0.0, -0.5], [0.5, 0.5])
+    ]);
+
+    let far_corner = mesh.vertices.last().unwrap();
+    assert_eq!(far_corner.position, [1.5, 0.0, -1.5]);
+    assert_eq!(far_corner.uv, [1.5, 1.5]);
+}
+
+#[test]
+fn triangle_list_gets_sequential_indices() {
+    let mesh = IndexedMesh::from_triangle_list(vec![1u8, 2, 3, 4, 5, 6]);
+
+    assert_eq!(mesh.indices, vec![0, 1, 2, 3, 4, 5]);
+    assert_eq!(mesh.vertex_bytes(), 6);
+    assert_eq!(mesh.index_bytes(), 24);
+}
@@ -0,0 +1,85 @@
+extern crate cgmath;
+extern crate vulrust;
+
+use cgmath::{Matrix4, Vector4};
+
+use vulrust::gltf_loader::{load_gltf, GltfError};
+
+
+#[test]
+fn loads_nested_node_transforms() {
+    let scene = load_gltf("fixtures/gltf/triangle.gltf").unwrap();
+
+    assert_eq!(scene.primitives.len(), 1);
+    let primitive = &scene.primitives[0];
+    assert_eq!(primitive.mesh, "triangle");
+
+    // The child scales by 2, its parent then moves it 1 along x.
+    let expected = Matrix4::from_translation([1.0, 0.0, 0.0].into()) * Matrix4::from_scale(2.0);
+    assert_eq!(primitive.transform, expected);
+    assert_eq!(primitive.transform * Vector4::new(1.0, 0.0, 0.0, 1.0), Vector4::new(3.0, 0.0, 0.0, 1.0));
+}
+
+#[test]
+fn keeps_primitive_indices() {
+    let scene = load_gltf("fixtures/gltf/triangle.gltf").unwrap();
+    let geometry = &scene.primitives[0].geometry;
+    let vertices = &geometry.vertices;
+
+    assert_eq!(geometry.indices, vec![0, 1, 2]);
+    assert_eq!(vertices.len(), 3);
+    assert_eq!(vertices[1].position, [1.0, 0.0, 0.0]);
+    assert_eq!(vertices[2].position, [0.0, 1.0, 0.0]);
+    // No normals or UVs in the file.
+    for vertex in vertices.iter() {
+        assert_eq!(vertex.normal, [0.0, 0.0, 1.0]);
+        assert_eq!(vertex.uv, [0.0, 0.0]);
+    }
+}
+
+#[test]
+fn reads_base_color_material() {
+    let scene = load_gltf("fixtures/gltf/triangle.gltf").unwrap();
+
+    assert_eq!(scene.primitives[0].base_color, [0.0, 1.0, 0.0, 1.0]);
+    assert_eq!(scene.primitives[0].base_color_texture, None);
+    assert!(scene.textures.is_empty());
+}
+
+#[test]
+fn reports_missing_file() {
+    match load_gltf("fixtures/gltf/missing.gltf") {
+        Err(GltfError::Import(..)) => (),
+        other => panic!("expected an import error, got {:?}", other.map(|_| ()))
+    }
+}
+
+#[test]
+fn converts_strips_and_skips_points() {
+    let scene = load_gltf("fixtures/gltf/strip.gltf").unwrap();
+
+    // The points primitive isn't loaded.
+    assert_eq!(scene.primitives.len(), 1);
+    let geometry = &scene.primitives[0].geometry;
+    assert_eq!(geometry.indices, vec![0, 1, 2, 2, 1, 3]);
+    assert_eq!(scene.primitives[0].base_color_texture, Some(0));
+    assert_eq!(scene.textures.len(), 1);
+    // The base color texture is sampled with the second UV set.
+    assert_eq!(geometry.vertices[3].uv, [1.0, 1.0]);
+}
+
+#[test]
+fn reports_out_of_range_indices() {
+    match load_gltf("fixtures/gltf/bad_index.gltf") {
+        Err(GltfError::IndexOutOfRange { index, positions, .. }) => assert_eq!((index, positions), (5, 3)),
+        other => panic!("expected an index out of range, got {:?}", other.map(|_| ()))
+    }
+}
+
+#[test]
+fn reports_attribute_count_mismatches() {
+    match load_gltf("fixtures/gltf/short_normals.gltf") {
+        Err(GltfError::AttributeCount { attribute, count, positions, .. }) => assert_eq!((attribute, count, positions), ("normals", 2, 3)),
+        other => panic!("expected an attribute count mismatch, got {:?}", other.map(|_| ()))
+    }
+}
@@ -0,0 +1,117 @@
+// Golden-image regression tests for the render output.
+//
+// Every test renders a fixed scene with the headless mode of the binary and compares it
+// against the reference PNG in `tests/golden/`. They need a Vulkan device (lavapipe is fine),
+// so they are ignored by default: run them with `cargo test -- --ignored`.
+//
+// After an intended change of the output, regenerate the references with
+// `VULRUST_BLESS=1 cargo test -- --ignored` and check the new PNGs in.
+//
+// With `VULRUST_VALIDATION=strict` the renders also run under the validation layer and a test
+// fails if the layer reports an error.
+extern crate image;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use image::{ImageBuffer, Rgba, RgbaImage};
+
+
+// Max per-channel difference for two pixels to be considered equal.
+const CHANNEL_TOLERANCE: u8 = 8;
+// Share of pixels allowed to exceed the tolerance, covers rasterization differences on edges.
+const MAX_MISMATCH_RATIO: f64 = 0.001;
+const SEED: &str = "42";
+
+fn output_dir() -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden");
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn render(scene: &str) -> PathBuf {
+    let path = output_dir().join(format!("{}.png", scene));
+    let status = Command::new(env!("CARGO_BIN_EXE_vulrust"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .arg("--headless").arg(&path)
+        .arg("--scene").arg(scene)
+        .arg("--seed").arg(SEED)
+        .status()
+        .unwrap();
+    assert!(status.success(), "headless render of {:?} failed: {}", scene, status);
+    path
+}
+
+// Writes an image that is black where the frames match and red where they don't.
+fn diff_image(actual: &RgbaImage, expected: &RgbaImage) -> (RgbaImage, usize) {
+    let mut mismatched = 0;
+    let diff = ImageBuffer::from_fn(actual.width(), actual.height(), |x, y| {
+        let a = actual.get_pixel(x, y).data;
+        let e = expected.get_pixel(x, y).data;
+        let max = (0 .. 4).map(|i| a[i].abs_diff(e[i])).max().unwrap();
+        if max > CHANNEL_TOLERANCE {
+            mismatched += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            Rgba([0, 0, 0, 255])
+        }
+    });
+    (diff, mismatched)
+}
+
+fn check_golden(scene: &str) {
+    let actual_path = render(scene);
+    let expected_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{}.png", scene));
+
+    if env::var("VULRUST_BLESS").is_ok() {
+        fs::create_dir_all(expected_path.parent().unwrap()).unwrap();
+        fs::copy(&actual_path, &expected_path).unwrap();
+        println!("Blessed {:?}", expected_path);
+        return;
+    }
+
+    if !expected_path.exists() {
+        panic!("Missing reference {:?}, render it with VULRUST_BLESS=1", expected_path);
+    }
+
+    let actual = image::open(&actual_path).unwrap().to_rgba();
+    let expected = image::open(&expected_path).unwrap().to_rgba();
+    assert_eq!(actual.dimensions(), expected.dimensions(), "frame size of {:?} changed", scene);
+
+    let (diff, mismatched) = diff_image(&actual, &expected);
+    let total = (actual.width() * actual.height()) as usize;
+    if mismatched as f64 > total as f64 * MAX_MISMATCH_RATIO {
+        let diff_path = output_dir().join(format!("{}-diff.png", scene));
+        diff.save(&diff_path).unwrap();
+        panic!(
+            "{:?} differs from the reference in {} of {} pixels, see {:?} (actual frame {:?})",
+            scene, mismatched, total, diff_path, actual_path
+        );
+    }
+}
+
+#[test]
+#[ignore = "needs a Vulkan device"]
+fn golden_terrain() {
+    check_golden("terrain");
+}
+
+#[test]
+#[ignore = "needs a Vulkan device"]
+fn golden_cube() {
+    check_golden("cube");
+}
+
+#[test]
+#[ignore = "needs a Vulkan device"]
+fn golden_rectangles() {
+    check_golden("rectangles");
+}
+
+#[test]
+#[ignore = "needs a Vulkan device"]
+fn golden_all() {
+    check_golden("all");
+}
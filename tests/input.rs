@@ -0,0 +1,127 @@
+extern crate vulrust;
+extern crate winit;
+
+use winit::{MouseButton, VirtualKeyCode};
+
+use vulrust::input::{Action, Binding, InputMap, InputMapError, InputState};
+
+
+#[test]
+fn default_map_keeps_the_old_keys() {
+    let mut input = InputState::new(InputMap::default());
+
+    input.press(Binding::Key(VirtualKeyCode::W));
+    assert!(input.held(Action::MoveForward));
+    input.release(Binding::Key(VirtualKeyCode::W));
+    input.press(Binding::Key(VirtualKeyCode::Up));
+    assert!(input.held(Action::MoveForward));
+    assert!(!input.held(Action::MoveBackward));
+}
+
+#[test]
+fn pressed_and_released_last_one_frame() {
+    let mut input = InputState::new(InputMap::default());
+    let g = Binding::Key(VirtualKeyCode::G);
+
+    input.press(g);
+    assert!(input.pressed(Action::ToggleWalking));
+    assert!(input.held(Action::ToggleWalking));
+    input.end_frame();
+
+    // Key repeat sends more presses while the key stays down.
+    input.press(g);
+    assert!(!input.pressed(Action::ToggleWalking));
+    assert!(input.held(Action::ToggleWalking));
+
+    input.release(g);
+    assert!(input.released(Action::ToggleWalking));
+    assert!(!input.held(Action::ToggleWalking));
+    input.end_frame();
+    assert!(!input.released(Action::ToggleWalking));
+}
+
+#[test]
+fn tap_within_a_frame_still_counts() {
+    let mut input = InputState::new(InputMap::default());
+
+    input.press(Binding::Key(VirtualKeyCode::Tab));
+    input.release(Binding::Key(VirtualKeyCode::Tab));
+    assert!(input.pressed(Action::ToggleCursor));
+    assert!(input.released(Action::ToggleCursor));
+    assert!(!input.held(Action::ToggleCursor));
+}
+
+#[test]
+fn config_replaces_listed_actions_only() {
+    let map = InputMap::load("fixtures/input/bindings.cfg").unwrap();
+    let mut input = InputState::new(map);
+
+    input.press(Binding::Key(VirtualKeyCode::W));
+    input.press(Binding::Key(VirtualKeyCode::G));
+    assert!(!input.held(Action::MoveForward));
+    assert!(!input.held(Action::ToggleWalking));
+
+    input.press(Binding::Mouse(MouseButton::Right));
+    assert!(input.held(Action::MoveForward));
+    input.press(Binding::Key(VirtualKeyCode::S));
+    assert!(input.held(Action::MoveBackward));
+}
+
+#[test]
+fn axes_hold_past_the_threshold() {
+    let mut input = InputState::new(InputMap::load("fixtures/input/bindings.cfg").unwrap());
+
+    input.set_axis(0, -0.2);
+    assert!(!input.held(Action::TurnLeft));
+    input.set_axis(0, -0.9);
+    assert!(input.held(Action::TurnLeft));
+    assert!(!input.held(Action::TurnRight));
+    input.set_axis(0, 0.9);
+    assert!(input.released(Action::TurnLeft));
+    assert!(input.held(Action::TurnRight));
+}
+
+#[test]
+fn release_all_lets_go_of_everything() {
+    let mut input = InputState::new(InputMap::default());
+
+    input.press(Binding::Key(VirtualKeyCode::A));
+    input.press(Binding::Key(VirtualKeyCode::D));
+    input.release_all();
+    assert!(!input.held(Action::StrafeLeft));
+    assert!(!input.held(Action::StrafeRight));
+    assert!(input.released(Action::StrafeLeft));
+}
+
+#[test]
+fn parses_binding_names() {
+    assert_eq!(Binding::from_name("LShift"), Some(Binding::Key(VirtualKeyCode::LShift)));
+    assert_eq!(Binding::from_name("Key1"), Some(Binding::Key(VirtualKeyCode::Key1)));
+    assert_eq!(Binding::from_name("MouseMiddle"), Some(Binding::Mouse(MouseButton::Middle)));
+    assert_eq!(Binding::from_name("Mouse4"), Some(Binding::Mouse(MouseButton::Other(4))));
+    assert_eq!(Binding::from_name("Axis12+"), Some(Binding::Axis { axis: 12, positive: true }));
+    assert_eq!(Binding::from_name("Axis1"), None);
+    assert_eq!(Binding::from_name("Axis+"), None);
+    assert_eq!(Binding::from_name("Axis3-"), Some(Binding::Axis { axis: 3, positive: false }));
+    // Non-ASCII names are unknown, not a panic on a char boundary.
+    assert_eq!(Binding::from_name("Axisé"), None);
+    assert_eq!(Binding::from_name("Axis1é"), None);
+    assert_eq!(Binding::from_name("Mouseé"), None);
+    assert_eq!(Binding::from_name("Shift"), None);
+}
+
+#[test]
+fn reports_bad_lines() {
+    match InputMap::parse("MoveForward = W\nJump = Space") {
+        Err(InputMapError::UnknownAction(2, ref name)) if name == "Jump" => (),
+        other => panic!("expected an unknown action, got {:?}", other)
+    }
+    match InputMap::parse("MoveForward = W, Whoops") {
+        Err(InputMapError::UnknownBinding(1, ref name)) if name == "Whoops" => (),
+        other => panic!("expected an unknown binding, got {:?}", other)
+    }
+    match InputMap::parse("\n\nMoveForward W") {
+        Err(InputMapError::Syntax(3, _)) => (),
+        other => panic!("expected a syntax error, got {:?}", other)
+    }
+}
@@ -0,0 +1,58 @@
+extern crate cgmath;
+extern crate vulrust;
+
+use cgmath::Rad;
+
+use vulrust::mouse::{MouseConfig, WheelAction};
+use vulrust::world::World;
+
+
+#[test]
+fn mouse_motion_turns_and_pitches() {
+    let mouse = MouseConfig { sensitivity: Rad(0.01), .. MouseConfig::default() };
+    let mut world = World::new();
+    let (yaw, pitch) = (world.camera.yaw, world.camera.pitch);
+
+    // Right and forwards, away from the user.
+    mouse.look(&mut world, 10.0, -5.0);
+    assert!((world.camera.yaw.0 - (yaw.0 + 0.1)).abs() < 1e-5);
+    assert!((world.camera.pitch.0 - (pitch.0 + 0.05)).abs() < 1e-5);
+}
+
+#[test]
+fn invert_y_flips_pitch_only() {
+    let mouse = MouseConfig { sensitivity: Rad(0.01), invert_y: true, .. MouseConfig::default() };
+    let mut world = World::new();
+    let (yaw, pitch) = (world.camera.yaw, world.camera.pitch);
+
+    mouse.look(&mut world, 10.0, -5.0);
+    assert!((world.camera.yaw.0 - (yaw.0 + 0.1)).abs() < 1e-5);
+    assert!((world.camera.pitch.0 - (pitch.0 - 0.05)).abs() < 1e-5);
+}
+
+#[test]
+fn wheel_changes_speed_or_fov() {
+    let mut world = World::new();
+    let (move_speed, fovy) = (world.move_speed, world.fovy());
+
+    MouseConfig::default().scroll(&mut world, 2.0);
+    assert!(world.move_speed > move_speed);
+    assert_eq!(world.fovy(), fovy);
+
+    let zoom = MouseConfig { wheel: WheelAction::Fov, .. MouseConfig::default() };
+    zoom.scroll(&mut world, 2.0);
+    assert!(world.fovy() < fovy);
+
+    // Both stay within sane limits however far the wheel goes.
+    zoom.scroll(&mut world, 1000.0);
+    assert!(world.fovy().0 > 0.0);
+    MouseConfig::default().scroll(&mut world, -1000.0);
+    assert!(world.move_speed > 0.0);
+}
+
+#[test]
+fn parses_wheel_actions() {
+    assert_eq!(WheelAction::from_name("speed"), Some(WheelAction::MoveSpeed));
+    assert_eq!(WheelAction::from_name("fov"), Some(WheelAction::Fov));
+    assert_eq!(WheelAction::from_name("zoom"), None);
+}
@@ -0,0 +1,119 @@
+extern crate vulrust;
+
+use std::path::Path;
+
+use vulrust::obj_loader::{load_obj, parse_obj, ObjError};
+
+
+#[test]
+fn loads_cube_with_material() {
+    let model = load_obj("fixtures/obj/cube.obj").unwrap();
+
+    assert_eq!(model.meshes.len(), 1);
+    let mesh = &model.meshes[0];
+    assert_eq!(mesh.name, "cube");
+    assert_eq!(mesh.material, Some(String::from("red")));
+    // Six quads, two triangles each.
+    assert_eq!(mesh.vertices.len(), 36);
+    assert_eq!(model.diffuse(mesh), Some([0.8, 0.1, 0.1]));
+
+    let first = &mesh.vertices[0];
+    assert_eq!(first.position, [0.0, 0.0, 0.0]);
+    assert_eq!(first.normal, [0.0, 0.0, -1.0]);
+    assert_eq!(first.uv, [0.0, 0.0]);
+}
+
+#[test]
+fn splits_objects_and_groups() {
+    let model = load_obj("fixtures/obj/groups.obj").unwrap();
+
+    let names: Vec<&str> = model.meshes.iter().map(|mesh| mesh.name.as_str()).collect();
+    assert_eq!(names, vec!["triangle", "pentagon"]);
+    assert_eq!(model.meshes[0].vertices.len(), 3);
+    // A pentagon is triangulated into three triangles.
+    assert_eq!(model.meshes[1].vertices.len(), 9);
+    assert_eq!(model.meshes[1].vertices[0].position, [0.0, 0.0, 1.0]);
+    assert_eq!(model.meshes[1].vertices[8].position, [-0.5, 1.0, 1.0]);
+}
+
+#[test]
+fn computes_face_normals_when_missing() {
+    let model = load_obj("fixtures/obj/groups.obj").unwrap();
+
+    for vertex in model.meshes[0].vertices.iter() {
+        assert_eq!(vertex.normal, [0.0, 0.0, 1.0]);
+        assert_eq!(vertex.uv, [0.0, 0.0]);
+    }
+}
+
+#[test]
+fn converts_to_position_normal_vertices() {
+    let model = load_obj("fixtures/obj/cube.obj").unwrap();
+    let vertices = model.meshes[0].to_vertex3d_normal3d();
+
+    assert_eq!(vertices.len(), 36);
+    assert_eq!(vertices[5].position, model.meshes[0].vertices[5].position);
+    assert_eq!(vertices[5].normal, model.meshes[0].vertices[5].normal);
+}
+
+#[test]
+fn reports_out_of_range_index_with_line() {
+    match load_obj("fixtures/obj/malformed.obj") {
+        Err(ObjError::Parse { line, message, .. }) => {
+            assert_eq!(line, 4);
+            assert!(message.contains("out of range"), "{}", message);
+        },
+        other => panic!("expected a parse error, got {:?}", other)
+    }
+}
+
+#[test]
+fn reports_malformed_lines() {
+    let path = Path::new("inline.obj");
+
+    let errors = vec![
+        ("v 1.0 2.0\n", 1),
+        ("v 1.0 2.0 x\n", 1),
+        ("v 0 0 0\nv 1 0 0\nf 1 2\n", 3),
+        ("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1/1 2/1 3/1\n", 4),
+        ("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 0 1 2\n", 4),
+        ("usemtl\n", 1)
+    ];
+    for (source, expected_line) in errors {
+        match parse_obj(source, path) {
+            Err(ObjError::Parse { line, .. }) => assert_eq!(line, expected_line, "{:?}", source),
+            other => panic!("expected a parse error for {:?}, got {:?}", source, other)
+        }
+    }
+}
+
+#[test]
+fn reads_one_to_three_uv_values() {
+    let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0.5\nvt 0.25 0.75\nvt 1 0.5 0\nf 1/1 2/2 3/3\n";
+    let model = parse_obj(source, Path::new("inline.obj")).unwrap();
+    let vertices = &model.meshes[0].vertices;
+    assert_eq!(vertices[0].uv, [0.5, 0.0]);
+    assert_eq!(vertices[1].uv, [0.25, 0.75]);
+    assert_eq!(vertices[2].uv, [1.0, 0.5]);
+
+    match parse_obj("vt\n", Path::new("inline.obj")) {
+        Err(ObjError::Parse { line: 1, .. }) => (),
+        other => panic!("expected a parse error, got {:?}", other)
+    }
+}
+
+#[test]
+fn skips_unknown_keywords() {
+    let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvp 0.5 0.5\nmg 1 0.5\nbogus 1 2 3\nf 1 2 3\nbogus\n";
+    let model = parse_obj(source, Path::new("inline.obj")).unwrap();
+    assert_eq!(model.meshes.len(), 1);
+    assert_eq!(model.meshes[0].vertices.len(), 3);
+}
+
+#[test]
+fn reports_missing_file() {
+    match load_obj("fixtures/obj/missing.obj") {
+        Err(ObjError::Io(..)) => (),
+        other => panic!("expected an io error, got {:?}", other)
+    }
+}
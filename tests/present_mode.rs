@@ -0,0 +1,69 @@
+extern crate vulkano;
+extern crate vulrust;
+
+use vulkano::format::Format;
+use vulkano::swapchain::{ColorSpace, PresentMode, SupportedPresentModes};
+
+use vulrust::present_mode::{choose_surface_format, PresentPreference};
+
+
+fn modes(immediate: bool, mailbox: bool, relaxed: bool) -> SupportedPresentModes {
+    SupportedPresentModes {
+        immediate: immediate,
+        mailbox: mailbox,
+        fifo: true,
+        relaxed: relaxed,
+        shared_demand: false,
+        shared_continuous: false
+    }
+}
+
+#[test]
+fn parses_present_modes() {
+    assert_eq!(PresentPreference::parse("fifo"), Some(PresentPreference::Fifo));
+    assert_eq!(PresentPreference::parse("VSync"), Some(PresentPreference::Fifo));
+    assert_eq!(PresentPreference::parse("fifo-relaxed"), Some(PresentPreference::FifoRelaxed));
+    assert_eq!(PresentPreference::parse(" mailbox "), Some(PresentPreference::Mailbox));
+    assert_eq!(PresentPreference::parse("novsync"), Some(PresentPreference::Immediate));
+    assert_eq!(PresentPreference::parse("tearing"), None);
+    assert_eq!(PresentPreference::default(), PresentPreference::Fifo);
+}
+
+#[test]
+fn falls_back_to_supported_modes() {
+    let everything = modes(true, true, true);
+    assert_eq!(PresentPreference::Immediate.choose(&everything), PresentMode::Immediate);
+    assert_eq!(PresentPreference::FifoRelaxed.choose(&everything), PresentMode::Relaxed);
+    assert_eq!(PresentPreference::Fifo.choose(&everything), PresentMode::Fifo);
+
+    let mailbox_only = modes(false, true, false);
+    assert_eq!(PresentPreference::Immediate.choose(&mailbox_only), PresentMode::Mailbox);
+    assert_eq!(PresentPreference::FifoRelaxed.choose(&mailbox_only), PresentMode::Fifo);
+
+    let fifo_only = modes(false, false, false);
+    assert_eq!(PresentPreference::Immediate.choose(&fifo_only), PresentMode::Fifo);
+    assert_eq!(PresentPreference::Mailbox.choose(&fifo_only), PresentMode::Fifo);
+}
+
+#[test]
+fn cycles_through_every_preference() {
+    let mut preference = PresentPreference::Fifo;
+    let mut seen = vec![preference];
+    for _ in 0 .. 3 {
+        preference = preference.next();
+        assert!(!seen.contains(&preference));
+        seen.push(preference);
+    }
+    assert_eq!(preference.next(), PresentPreference::Fifo);
+}
+
+#[test]
+fn prefers_srgb_surface_formats() {
+    let formats = [
+        (Format::B8G8R8A8Unorm, ColorSpace::SrgbNonLinear),
+        (Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear)
+    ];
+    assert_eq!(choose_surface_format(&formats), Some((Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear)));
+    assert_eq!(choose_surface_format(&formats[.. 1]), Some((Format::B8G8R8A8Unorm, ColorSpace::SrgbNonLinear)));
+    assert_eq!(choose_surface_format(&[]), None);
+}
@@ -0,0 +1,136 @@
+extern crate vulkano;
+extern crate vulrust;
+
+use std::borrow::Cow;
+use std::path::Path;
+use std::vec::IntoIter;
+
+use vulkano::descriptor::descriptor::DescriptorType;
+use vulkano::format::Format;
+use vulkano::pipeline::shader::{ShaderInterfaceDef, ShaderInterfaceDefEntry};
+use vulkano::pipeline::vertex::{VertexMemberInfo, VertexMemberTy};
+
+use vulrust::shader_interface::{check_fragment_shader, check_vertex_shader, reflect, InterfaceError};
+use vulrust::shader_reload::{fragment_stage, load_shader, vertex_stage, ShaderStage};
+use vulrust::shader_utils::{fs_cube, vs_cube};
+
+
+// Inputs or outputs the way `shader!` describes them: location, format and name.
+struct Interface(&'static [(u32, Format, &'static str)]);
+
+unsafe impl ShaderInterfaceDef for Interface {
+    type Iter = IntoIter<ShaderInterfaceDefEntry>;
+
+    fn elements(&self) -> IntoIter<ShaderInterfaceDefEntry> {
+        self.0.iter().map(|&(location, format, name)| ShaderInterfaceDefEntry {
+            location: location .. location + 1,
+            format: format,
+            name: Some(Cow::Borrowed(name))
+        }).collect::<Vec<_>>().into_iter()
+    }
+}
+
+const CUBE_INPUTS: Interface = Interface(&[(0, Format::R32G32B32Sfloat, "position"), (1, Format::R32G32B32Sfloat, "normal")]);
+const CUBE_VARYINGS: Interface = Interface(&[(0, Format::R32G32B32Sfloat, "vNormal")]);
+const CUBE_COLOR: Interface = Interface(&[(0, Format::R32G32B32A32Sfloat, "f_color")]);
+
+// Fields of `Vertex3DNormal3D` and `Vertex3DUV`, as `Vertex::member` would report them.
+fn normal_vertex(name: &str) -> Option<VertexMemberInfo> {
+    match name {
+        "position" => Some(VertexMemberInfo { offset: 0, ty: VertexMemberTy::F32, array_size: 3 }),
+        "normal" => Some(VertexMemberInfo { offset: 12, ty: VertexMemberTy::F32, array_size: 3 }),
+        _ => None
+    }
+}
+
+fn uv_vertex(name: &str) -> Option<VertexMemberInfo> {
+    match name {
+        "position" => Some(VertexMemberInfo { offset: 0, ty: VertexMemberTy::F32, array_size: 3 }),
+        "uv" => Some(VertexMemberInfo { offset: 12, ty: VertexMemberTy::F32, array_size: 2 }),
+        _ => None
+    }
+}
+
+fn fixture(name: &str) -> Vec<u32> {
+    load_shader(&Path::new("fixtures/shaders").join(name)).unwrap()
+}
+
+#[test]
+fn reflects_vertex_shader() {
+    let interface = reflect(&fixture("cube.vert.spv"), ShaderStage::Vertex).unwrap();
+
+    let inputs: Vec<_> = interface.inputs.iter().map(|input| (input.location, input.format)).collect();
+    assert_eq!(inputs, vec![(0, Format::R32G32B32Sfloat), (1, Format::R32G32B32Sfloat)]);
+    assert_eq!(interface.outputs.len(), 1);
+    assert_eq!(interface.outputs[0].location, 0);
+
+    assert_eq!(interface.descriptors.len(), 1);
+    assert_eq!((interface.descriptors[0].set, interface.descriptors[0].binding), (0, 0));
+    assert_eq!(interface.descriptors[0].ty, DescriptorType::UniformBuffer);
+    assert_eq!(interface.push_constants_size, 0);
+}
+
+#[test]
+fn reflects_push_constants() {
+    let interface = reflect(&fixture("mesh.vert.spv"), ShaderStage::Vertex).unwrap();
+    // A mat4 and a vec4.
+    assert_eq!(interface.push_constants_size, 80);
+    assert_eq!(interface.inputs.len(), 3);
+
+    // The cube pipeline has no push constants to give.
+    let layout = vs_cube::Layout(vertex_stage());
+    assert_eq!(interface.check_layout(&layout, vertex_stage()), Err(InterfaceError::PushConstants { size: 80, available: 0 }));
+}
+
+#[test]
+fn accepts_matching_shaders() {
+    let layout = vs_cube::Layout(vertex_stage());
+    check_vertex_shader(&fixture("cube.vert.spv"), &CUBE_INPUTS, &CUBE_VARYINGS, &layout, normal_vertex).unwrap();
+
+    let layout = fs_cube::Layout(fragment_stage());
+    check_fragment_shader(&fixture("cube.frag.spv"), &CUBE_VARYINGS, &CUBE_COLOR, &layout).unwrap();
+}
+
+#[test]
+fn rejects_missing_vertex_members() {
+    let layout = vs_cube::Layout(vertex_stage());
+    let result = check_vertex_shader(&fixture("cube.vert.spv"), &CUBE_INPUTS, &CUBE_VARYINGS, &layout, uv_vertex);
+    assert_eq!(result, Err(InterfaceError::MissingVertexMember("normal".to_string())));
+}
+
+#[test]
+fn rejects_inputs_the_pipeline_does_not_feed() {
+    let layout = vs_cube::Layout(vertex_stage());
+    let result = check_vertex_shader(&fixture("mesh.vert.spv"), &CUBE_INPUTS, &CUBE_VARYINGS, &layout, normal_vertex);
+    match result {
+        Err(InterfaceError::UnexpectedInput { location: 2, .. }) => (),
+        other => panic!("expected an unexpected input at location 2, got {:?}", other)
+    }
+}
+
+#[test]
+fn rejects_wrong_stage_and_garbage() {
+    assert_eq!(reflect(&fixture("cube.vert.spv"), ShaderStage::Fragment), Err(InterfaceError::MissingEntryPoint(ShaderStage::Fragment)));
+    match reflect(&[0x0723_0203, 1, 2], ShaderStage::Vertex) {
+        Err(InterfaceError::Malformed(_)) => (),
+        other => panic!("expected malformed SPIR-V, got {:?}", other)
+    }
+}
+
+#[test]
+fn rejects_empty_vectors() {
+    // A vertex input at location 0 typed as a float vector without components.
+    let spirv = [
+        0x0723_0203, 0x0001_0000, 0, 11, 0,
+        (6 << 16) | 15, 0, 10, 0x6e69_616d, 0, 4,
+        (3 << 16) | 22, 1, 32,
+        (4 << 16) | 23, 2, 1, 0,
+        (4 << 16) | 32, 3, 1, 2,
+        (4 << 16) | 59, 3, 4, 1,
+        (4 << 16) | 71, 4, 30, 0
+    ];
+    match reflect(&spirv, ShaderStage::Vertex) {
+        Err(InterfaceError::Malformed(reason)) => assert_eq!(reason, "vector of 0 components"),
+        other => panic!("expected malformed SPIR-V, got {:?}", other)
+    }
+}
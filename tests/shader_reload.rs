@@ -0,0 +1,96 @@
+extern crate vulrust;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use vulrust::shader_reload::{load_shader, parse_file_name, ShaderError, ShaderLibrary, ShaderStage};
+
+
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+
+// Empty scratch directory for one test.
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vulrust-shader-reload-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn parses_shader_file_names() {
+    assert_eq!(parse_file_name(Path::new("shaders/cube.vert")), Some(("cube".to_string(), ShaderStage::Vertex, false)));
+    assert_eq!(parse_file_name(Path::new("mesh.frag.spv")), Some(("mesh".to_string(), ShaderStage::Fragment, true)));
+    assert_eq!(parse_file_name(Path::new("cube.glsl")), None);
+    assert_eq!(parse_file_name(Path::new("cube.spv")), None);
+}
+
+#[test]
+fn compiles_glsl_files() {
+    let spirv = load_shader(Path::new("fixtures/shaders/cube.vert")).unwrap();
+    assert_eq!(spirv[0], SPIRV_MAGIC);
+}
+
+#[test]
+fn rejects_files_that_are_not_spirv() {
+    let dir = scratch_dir("invalid");
+    let path = dir.join("cube.frag.spv");
+    fs::write(&path, b"#version 450\n").unwrap();
+
+    match load_shader(&path) {
+        Err(ShaderError::InvalidSpirv(ref invalid)) if invalid == &path => (),
+        other => panic!("expected invalid SPIR-V, got {:?}", other)
+    }
+}
+
+#[test]
+fn rejects_unrecognized_file_names() {
+    let dir = scratch_dir("unrecognized");
+    let path = dir.join("cube.glsl");
+    fs::write(&path, b"#version 450\n").unwrap();
+
+    match load_shader(&path) {
+        Err(ShaderError::UnrecognizedFileName(ref unrecognized)) if unrecognized == &path => (),
+        other => panic!("expected an unrecognized file name, got {:?}", other)
+    }
+}
+
+#[test]
+fn library_recompiles_changed_files_and_keeps_the_last_good_one() {
+    let dir = scratch_dir("library");
+    fs::copy("fixtures/shaders/cube.frag", dir.join("cube.frag")).unwrap();
+    fs::write(dir.join("notes.txt"), "not a shader").unwrap();
+    let mut library = ShaderLibrary::new(&dir);
+
+    let results = library.poll();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].as_ref().unwrap(), &dir.join("cube.frag"));
+    let compiled = library.spirv("cube", ShaderStage::Fragment).unwrap().to_vec();
+    assert!(library.spirv("cube", ShaderStage::Vertex).is_none());
+
+    // Nothing changed, nothing to do.
+    assert!(library.poll().is_empty());
+
+    fs::write(dir.join("cube.frag"), "#version 450\nvoid main() { this does not compile }\n").unwrap();
+    let results = library.poll();
+    assert_eq!(results.len(), 1);
+    match results[0] {
+        Err(ShaderError::Compile(ref path, _)) => assert_eq!(path, &dir.join("cube.frag")),
+        ref other => panic!("expected a compile error, got {:?}", other)
+    }
+    assert_eq!(library.spirv("cube", ShaderStage::Fragment).unwrap(), compiled.as_slice());
+
+    fs::copy("fixtures/shaders/cube.vert", dir.join("cube.vert")).unwrap();
+    let results = library.poll();
+    assert_eq!(results.len(), 1);
+    assert!(results[0].is_ok());
+    assert!(library.spirv("cube", ShaderStage::Vertex).is_some());
+}
+
+#[test]
+fn missing_directory_is_reported() {
+    let mut library = ShaderLibrary::new("fixtures/shaders/missing");
+    match library.poll().pop() {
+        Some(Err(ShaderError::Io(..))) => (),
+        other => panic!("expected an IO error, got {:?}", other)
+    }
+}
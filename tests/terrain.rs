@@ -0,0 +1,124 @@
+extern crate cgmath;
+extern crate vulrust;
+
+use cgmath::{InnerSpace, Point3, Vector3};
+
+use vulrust::terrain::{load_heightmap, Heightfield, Heightmap, HeightmapError, TerrainDesc};
+
+
+#[test]
+fn keeps_16_bit_precision() {
+    let heightmap = load_heightmap("fixtures/heightmaps/gray16.png").unwrap();
+
+    assert_eq!((heightmap.width, heightmap.height), (2, 2));
+    assert_eq!(heightmap.samples, vec![0, 65535, 32768, 1]);
+}
+
+#[test]
+fn widens_8_bit_samples() {
+    let heightmap = load_heightmap("fixtures/heightmaps/gray8.png").unwrap();
+
+    assert_eq!((heightmap.width, heightmap.height), (2, 2));
+    assert_eq!(heightmap.samples, vec![0, 65535, 128 * 257, 257]);
+}
+
+#[test]
+fn expands_palettes_with_transparency() {
+    // Palette of grays with a tRNS chunk, expands to RGBA rather than RGB.
+    let heightmap = load_heightmap("fixtures/heightmaps/indexed_trns.png").unwrap();
+
+    assert_eq!((heightmap.width, heightmap.height), (2, 2));
+    assert_eq!(heightmap.samples, vec![0, 64 * 257, 128 * 257, 65535]);
+}
+
+#[test]
+fn default_terrain_heightmap_loads() {
+    let terrain = TerrainDesc::default();
+    let heightmap = load_heightmap(&terrain.heightmap).unwrap();
+
+    assert_eq!((heightmap.width, heightmap.height), (464, 464));
+    assert_eq!(heightmap.samples.len(), 464 * 464);
+}
+
+#[test]
+fn reports_missing_file() {
+    match load_heightmap("fixtures/heightmaps/missing.png") {
+        Err(HeightmapError::Io(..)) => (),
+        other => panic!("expected an io error, got {:?}", other.map(|_| ()))
+    }
+}
+
+fn flat_desc() -> TerrainDesc {
+    TerrainDesc {
+        resolution: 4,
+        cell_size: 1.0,
+        height_scale: 2.0,
+        offset: [10.0, 1.0, -10.0],
+        .. TerrainDesc::default()
+    }
+}
+
+// Heights rise by about a quarter per texel along +x, every texel centre sits on a grid vertex.
+fn ramp() -> Heightmap {
+    Heightmap {
+        width: 4,
+        height: 4,
+        samples: (0 .. 16).map(|i| (i % 4) as u16 * 16384).collect()
+    }
+}
+
+#[test]
+fn heightfield_matches_fixture_texels() {
+    let desc = TerrainDesc::default();
+    let heightmap = load_heightmap(&desc.heightmap).unwrap();
+    let heightfield = Heightfield::new(heightmap.clone(), &desc);
+
+    // World position of the centre of texel (100, 200): u = 100.5 / 464, v = 200.5 / 464.
+    let size = desc.cell_size * desc.resolution as f32;
+    let x = 100.5 / 464.0 * size;
+    let z = -200.5 / 464.0 * size;
+    let expected = -(heightmap.samples[200 * 464 + 100] as f32 / 65535.0) * desc.height_scale;
+    assert!((heightfield.height_at(x, z).unwrap() - expected).abs() < 1e-4);
+
+    for &(x, z) in [(0.0, 0.0), (30.0, -40.0), (75.0, -75.0)].iter() {
+        let height = heightfield.height_at(x, z).unwrap();
+        assert!(height <= 0.0 && height >= -desc.height_scale, "{} at {:?}", height, (x, z));
+    }
+    assert_eq!(heightfield.height_at(-1.0, 0.0), None);
+    assert_eq!(heightfield.height_at(0.0, 1.0), None);
+}
+
+#[test]
+fn heightfield_interpolates_bilinearly() {
+    let heightfield = Heightfield::new(ramp(), &flat_desc());
+
+    // Texel centres 1 and 2 along x, halfway between them, all shifted by the offset.
+    let y1 = 1.0 - 0.25 * 2.0;
+    let y2 = 1.0 - 0.5 * 2.0;
+    assert!((heightfield.height_at(11.5, -10.5).unwrap() - y1).abs() < 1e-4);
+    assert!((heightfield.height_at(12.5, -10.5).unwrap() - y2).abs() < 1e-4);
+    assert!((heightfield.height_at(12.0, -12.0).unwrap() - (y1 + y2) * 0.5).abs() < 1e-4);
+}
+
+#[test]
+fn heightfield_normals_point_up_the_world() {
+    let flat = Heightfield::new(Heightmap { width: 2, height: 2, samples: vec![30000; 4] }, &flat_desc());
+    let normal = flat.normal_at(12.0, -12.0).unwrap();
+    assert!((normal - Vector3::new(0.0, -1.0, 0.0)).magnitude() < 1e-5);
+
+    // The ramp climbs towards -y along +x, so its normal leans back towards -x.
+    let normal = Heightfield::new(ramp(), &flat_desc()).normal_at(12.0, -11.5).unwrap();
+    let expected = Vector3::new(-0.5, -1.0, 0.0).normalize();
+    assert!((normal - expected).magnitude() < 1e-5, "{:?}", normal);
+}
+
+#[test]
+fn heightfield_intersects_rays() {
+    let heightfield = Heightfield::new(ramp(), &flat_desc());
+
+    let hit = heightfield.intersect_ray(Point3::new(12.0, -20.0, -12.0), Vector3::new(0.0, 1.0, 0.0), 100.0).unwrap();
+    assert!((hit.y - heightfield.height_at(12.0, -12.0).unwrap()).abs() < 1e-3);
+
+    assert_eq!(heightfield.intersect_ray(Point3::new(12.0, -20.0, -12.0), Vector3::new(0.0, -1.0, 0.0), 100.0), None);
+    assert_eq!(heightfield.intersect_ray(Point3::new(12.0, -20.0, -12.0), Vector3::new(0.0, 1.0, 0.0), 5.0), None);
+}
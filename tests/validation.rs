@@ -0,0 +1,65 @@
+extern crate vulkano;
+extern crate vulrust;
+
+use vulkano::instance::InstanceExtensions;
+use vulkano::instance::debug::MessageTypes;
+use vulkano::instance::layers_list;
+
+use vulrust::error::VulrustError;
+use vulrust::validation;
+use vulrust::validation::{Severity, Validation, ValidationMode, VALIDATION_LAYER};
+
+
+#[test]
+fn parses_validation_modes() {
+    assert_eq!(ValidationMode::parse("off"), Some(ValidationMode::Off));
+    assert_eq!(ValidationMode::parse(""), Some(ValidationMode::Off));
+    assert_eq!(ValidationMode::parse("1"), Some(ValidationMode::On));
+    assert_eq!(ValidationMode::parse(" On "), Some(ValidationMode::On));
+    assert_eq!(ValidationMode::parse("STRICT"), Some(ValidationMode::Strict));
+    assert_eq!(ValidationMode::parse("loud"), None);
+}
+
+#[test]
+fn picks_the_most_severe_flag() {
+    let warning_and_info = MessageTypes { warning: true, information: true, .. MessageTypes::none() };
+    assert_eq!(Severity::of(warning_and_info), Severity::Warning);
+    assert_eq!(Severity::of(MessageTypes::errors_and_warnings()), Severity::Error);
+    assert_eq!(Severity::of(MessageTypes { performance_warning: true, .. MessageTypes::none() }), Severity::Performance);
+    assert_eq!(Severity::of(MessageTypes::none()), Severity::Debug);
+}
+
+#[test]
+fn strict_mode_fails_on_errors_only() {
+    let strict = Validation::detached(ValidationMode::Strict);
+    strict.log.record(Severity::Warning, "Validation", "a warning");
+    strict.log.record(Severity::Info, "Loader", "some info");
+    assert_eq!(strict.log.warnings(), 1);
+    assert!(strict.check().is_ok());
+
+    strict.log.record(Severity::Error, "Validation", "an error");
+    match strict.check() {
+        Err(VulrustError::Validation(1)) => (),
+        other => panic!("expected a validation failure, got {:?}", other)
+    }
+
+    let on = Validation::detached(ValidationMode::On);
+    on.log.record(Severity::Error, "Validation", "an error");
+    assert_eq!(on.log.errors(), 1);
+    assert!(on.check().is_ok());
+}
+
+#[test]
+fn strict_mode_needs_the_layer() {
+    let installed = layers_list()
+        .map(|mut layers| layers.any(|layer| layer.name() == VALIDATION_LAYER))
+        .unwrap_or(false);
+    if installed {
+        return;
+    }
+    match validation::create_instance(InstanceExtensions::none(), ValidationMode::Strict) {
+        Err(VulrustError::ValidationUnavailable(what)) => assert_eq!(what, VALIDATION_LAYER),
+        Err(other) => panic!("expected the layer to be missing, got {:?}", other),
+        Ok(_) => panic!("expected the layer to be missing")
+    }
+}
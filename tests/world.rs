@@ -0,0 +1,157 @@
+extern crate vulrust;
+
+use vulrust::terrain::{Heightfield, Heightmap, TerrainDesc};
+use vulrust::world::World;
+
+
+// One frame at 60 FPS.
+const FRAME: f32 = 1.0 / 60.0;
+
+// 16 by 16 quads of size 1 centred under the starting eye, `samples` fill a 2 by 2 heightmap
+// whose rows sit at z = 11 and z = 3.
+fn ground(samples: Vec<u16>, height_scale: f32) -> Heightfield {
+    let desc = TerrainDesc {
+        resolution: 16,
+        cell_size: 1.0,
+        height_scale: height_scale,
+        offset: [-6.0, 0.0, 15.0],
+        .. TerrainDesc::default()
+    };
+    Heightfield::new(Heightmap { width: 2, height: 2, samples: samples }, &desc)
+}
+
+fn walking_world(samples: Vec<u16>, height_scale: f32) -> World {
+    let mut world = World::new();
+    world.ground = Some(ground(samples, height_scale));
+    world.toggle_walking();
+    world
+}
+
+#[test]
+fn walking_settles_above_the_ground() {
+    let mut world = walking_world(vec![32768; 4], 4.0);
+    let ground = -(32768.0 / 65535.0) * 4.0;
+
+    world.follow_ground(FRAME);
+    let first = world.eye_position().y;
+    assert!(first > -6.0 && first < ground - 1.0, "moves part of the way, got {}", first);
+
+    for _ in 0 .. 200 {
+        world.follow_ground(FRAME);
+    }
+    assert!((world.eye_position().y - (ground - 1.0)).abs() < 1e-3);
+}
+
+#[test]
+fn flying_ignores_the_ground() {
+    let mut world = walking_world(vec![65535; 4], 4.0);
+    world.toggle_walking();
+
+    let before = world.eye_position();
+    world.follow_ground(FRAME);
+    assert_eq!(world.eye_position(), before);
+
+    // Flying forwards follows the pitch, the starting camera looks down at the ground.
+    world.move_forwards(FRAME);
+    let after = world.eye_position();
+    assert!(after.y > before.y);
+    assert!(before.z - after.z > 0.1);
+}
+
+#[test]
+fn walking_refuses_too_steep_slopes() {
+    // Rises 40 over the 8 units between the rows, straight ahead of the eye.
+    let mut world = walking_world(vec![0, 0, 65535, 65535], 40.0);
+    for _ in 0 .. 200 {
+        world.follow_ground(FRAME);
+    }
+
+    let mut previous = world.eye_position();
+    let mut blocked = false;
+    for _ in 0 .. 100 {
+        world.move_forwards(FRAME);
+        let eye = world.eye_position();
+        if eye == previous {
+            blocked = true;
+            break;
+        }
+        previous = eye;
+    }
+    assert!(blocked, "walked up the wall to {:?}", previous);
+}
+
+#[test]
+fn walking_climbs_gentle_slopes() {
+    // Rises 4 over 8 units, about 27 degrees.
+    let mut world = walking_world(vec![0, 0, 65535, 65535], 4.0);
+    for _ in 0 .. 200 {
+        world.follow_ground(FRAME);
+    }
+
+    let before = world.eye_position();
+    for _ in 0 .. 10 {
+        world.move_forwards(FRAME);
+        world.follow_ground(FRAME);
+    }
+    let after = world.eye_position();
+    assert!(before.z - after.z > 1.9, "only got from {:?} to {:?}", before, after);
+    assert!(after.y < before.y);
+}
+
+#[test]
+fn movement_does_not_depend_on_the_frame_rate() {
+    let mut slow = World::new();
+    let mut fast = World::new();
+
+    for _ in 0 .. 30 {
+        slow.move_forwards(1.0 / 30.0);
+        slow.turn_left(1.0 / 30.0);
+    }
+    for _ in 0 .. 144 {
+        fast.move_forwards(1.0 / 144.0);
+        fast.turn_left(1.0 / 144.0);
+    }
+    assert!((slow.camera.yaw.0 - fast.camera.yaw.0).abs() < 1e-4);
+    // Turning while moving curves the path a little differently, but not by much.
+    let distance = slow.eye_position() - fast.eye_position();
+    assert!(distance.x.abs() < 0.2 && distance.y.abs() < 0.2 && distance.z.abs() < 0.2, "{:?}", distance);
+
+    let (mut a, mut b) = (World::new(), World::new());
+    a.move_forwards(1.0);
+    for _ in 0 .. 60 {
+        b.move_forwards(1.0 / 60.0);
+    }
+    let distance = a.eye_position() - b.eye_position();
+    assert!(distance.x.abs() < 1e-3 && distance.y.abs() < 1e-3 && distance.z.abs() < 1e-3);
+}
+
+#[test]
+fn walking_settles_at_the_same_pace_at_any_frame_rate() {
+    let mut slow = walking_world(vec![32768; 4], 4.0);
+    let mut fast = walking_world(vec![32768; 4], 4.0);
+
+    for _ in 0 .. 3 {
+        slow.follow_ground(0.1);
+    }
+    for _ in 0 .. 30 {
+        fast.follow_ground(0.01);
+    }
+    assert!((slow.eye_position().y - fast.eye_position().y).abs() < 1e-3);
+}
+
+#[test]
+fn frames_blend_between_updates() {
+    let mut world = World::new();
+    world.begin_tick();
+    let before = world.view_projection();
+    world.move_forwards(1.0);
+    world.animate(1.0);
+    let after = world.view_projection();
+
+    world.alpha = 0.0;
+    assert_eq!(world.view_projection(), before);
+    assert_eq!(world.spin(), 0.0);
+    world.alpha = 0.5;
+    assert!(world.view_projection() != before && world.view_projection() != after);
+    assert_eq!(world.spin(), world.delta * 0.5);
+}